@@ -46,6 +46,9 @@ pub struct AccountCommand {
 pub enum AccountSubCommand {
     Create(account::AccountCreateCommand),
     Password(account::AccountPasswordCommand),
+    Close(account::AccountCloseCommand),
+    Merge(account::AccountMergeCommand),
+    Username(account::AccountUsernameCommand),
     Mint(MintCommand),
 }
 
@@ -61,6 +64,7 @@ pub enum DeviceSubCommand {
     Remove(device::DeviceRemoveCommand),
     List(device::DeviceListCommand),
     Paperkey(device::DevicePaperkeyCommand),
+    EncryptionKey(device::DeviceEncryptionKeyCommand),
 }
 
 #[derive(Clone, Debug, Clap)]
@@ -72,8 +76,18 @@ pub struct IdCommand {
 #[derive(Clone, Debug, Clap)]
 pub enum IdSubCommand {
     List(id::IdListCommand),
+    Audit(id::IdAuditCommand),
     Prove(id::IdProveCommand),
     Revoke(id::IdRevokeCommand),
+    Renew(id::IdRenewCommand),
+    Checkpoint(id::IdCheckpointCommand),
+    Attach(id::IdAttachCommand),
+    SshKeys(id::IdSshKeysCommand),
+    Track(id::IdTrackCommand),
+    Sign(id::IdSignCommand),
+    Verify(id::IdVerifyCommand),
+    Encrypt(id::IdEncryptCommand),
+    Decrypt(id::IdDecryptCommand),
 }
 
 #[derive(Clone, Debug, Clap)]
@@ -1,14 +1,28 @@
 use crate::command::*;
-use async_std::task;
+use async_std::{future, task};
 use clap::Clap;
 use std::time::Duration;
 use sunshine_cli_utils::{set_key, Client as _, ConfigDirNotFound, Result};
 use sunshine_faucet_cli::MintCommand;
 use sunshine_identity_cli::key::KeySetCommand;
-use test_client::{identity::IdentityClient, Client};
+use test_client::identity::{IdentityClient, PasswordUpdate};
+use test_client::Client;
 
 mod command;
 
+/// How often the `run` command rotates revoked team keys.
+const ROTATE_INTERVAL: Duration = Duration::from_secs(10);
+
+async fn update_password(client: &mut Client) -> Result<()> {
+    if client.update_password().await? == PasswordUpdate::ReprovisionRequired {
+        println!(
+            "Your device missed too many password changes. \
+             Provision it again from another device."
+        );
+    }
+    Ok(())
+}
+
 #[async_std::main]
 async fn main() -> Result<()> {
     env_logger::init();
@@ -30,7 +44,7 @@ async fn main() -> Result<()> {
 
     let mut password_changes = if client.chain_signer().is_ok() {
         let sub = client.subscribe_password_changes().await?;
-        client.update_password().await?;
+        update_password(&mut client).await?;
         Some(sub)
     } else {
         None
@@ -56,6 +70,9 @@ async fn main() -> Result<()> {
         SubCommand::Account(AccountCommand { cmd }) => match cmd {
             AccountSubCommand::Create(cmd) => cmd.exec(&client).await,
             AccountSubCommand::Password(cmd) => cmd.exec(&client).await,
+            AccountSubCommand::Close(cmd) => cmd.exec(&client).await,
+            AccountSubCommand::Merge(cmd) => cmd.exec(&client).await,
+            AccountSubCommand::Username(cmd) => cmd.exec(&client).await,
             AccountSubCommand::Mint(cmd) => cmd.exec(&client).await,
         },
         SubCommand::Device(DeviceCommand { cmd }) => match cmd {
@@ -63,11 +80,22 @@ async fn main() -> Result<()> {
             DeviceSubCommand::Remove(cmd) => cmd.exec(&client).await,
             DeviceSubCommand::List(cmd) => cmd.exec(&client).await,
             DeviceSubCommand::Paperkey(cmd) => cmd.exec(&client).await,
+            DeviceSubCommand::EncryptionKey(cmd) => cmd.exec(&client).await,
         },
         SubCommand::Id(IdCommand { cmd }) => match cmd {
             IdSubCommand::List(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Audit(cmd) => cmd.exec(&client).await,
             IdSubCommand::Prove(cmd) => cmd.exec(&client).await,
             IdSubCommand::Revoke(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Renew(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Checkpoint(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Attach(cmd) => cmd.exec(&client).await,
+            IdSubCommand::SshKeys(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Track(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Sign(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Verify(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Encrypt(cmd) => cmd.exec(&client).await,
+            IdSubCommand::Decrypt(cmd) => cmd.exec(&client).await,
         },
         SubCommand::Wallet(WalletCommand { cmd }) => match cmd {
             WalletSubCommand::Balance(cmd) => cmd.exec(&client).await,
@@ -75,8 +103,12 @@ async fn main() -> Result<()> {
         },
         SubCommand::Run => loop {
             if let Some(sub) = password_changes.as_mut() {
-                if sub.next().await.is_some() {
-                    client.update_password().await?;
+                if let Ok(Some(_)) = future::timeout(ROTATE_INTERVAL, sub.next()).await {
+                    update_password(&mut client).await?;
+                }
+                // Members that left a team can't rotate its key.
+                if let Err(err) = client.rotate_team_keys().await {
+                    eprintln!("failed to rotate team keys: {}", err);
                 }
             } else {
                 task::sleep(Duration::from_millis(100)).await
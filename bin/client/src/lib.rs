@@ -18,7 +18,9 @@ use sunshine_client_utils::{
     OffchainClient as OffchainClientT, OffchainStore,
 };
 use sunshine_faucet_client::Faucet;
-use sunshine_identity_client::{Claim, Identity};
+use sunshine_identity_client::{
+    Claim, EncryptedMessage, Identity, ProofCache, ServiceIndex, TrackSnapshot,
+};
 
 pub use sunshine_chain_client as chain;
 pub use sunshine_client_utils as client;
@@ -52,6 +54,7 @@ impl Chain for Runtime {
     type Number = u64;
     type TrieHasher = TreeHasherBlake2b256;
     type TrieHash = TreeHashBlake2b256;
+    type Team = Uid;
 }
 
 impl Faucet for Runtime {}
@@ -72,6 +75,10 @@ impl substrate_subxt::Runtime for Runtime {
 pub struct OffchainClient<S> {
     store: S,
     claims: IpldCache<S, DagCborCodec, Claim>,
+    proofs: IpldCache<S, DagCborCodec, ProofCache>,
+    index: IpldCache<S, DagCborCodec, ServiceIndex>,
+    tracks: IpldCache<S, DagCborCodec, TrackSnapshot>,
+    team_keys: IpldCache<S, DagCborCodec, EncryptedMessage>,
 }
 
 impl<S> Deref for OffchainClient<S> {
@@ -83,11 +90,19 @@ impl<S> Deref for OffchainClient<S> {
 }
 
 derive_cache!(OffchainClient, claims, DagCborCodec, Claim);
+derive_cache!(OffchainClient, proofs, DagCborCodec, ProofCache);
+derive_cache!(OffchainClient, index, DagCborCodec, ServiceIndex);
+derive_cache!(OffchainClient, tracks, DagCborCodec, TrackSnapshot);
+derive_cache!(OffchainClient, team_keys, DagCborCodec, EncryptedMessage);
 
 impl<S: Store> OffchainClient<S> {
     pub fn new(store: S) -> Self {
         Self {
             claims: IpldCache::new(store.clone(), DagCborCodec, BLAKE2B_256, 64),
+            proofs: IpldCache::new(store.clone(), DagCborCodec, BLAKE2B_256, 4),
+            index: IpldCache::new(store.clone(), DagCborCodec, BLAKE2B_256, 4),
+            tracks: IpldCache::new(store.clone(), DagCborCodec, BLAKE2B_256, 16),
+            team_keys: IpldCache::new(store.clone(), DagCborCodec, BLAKE2B_256, 16),
             store,
         }
     }
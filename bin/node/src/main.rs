@@ -15,6 +15,8 @@ pub struct Cli {
 #[derive(Debug, StructOpt)]
 pub enum Subcommand {
     PurgeChain(sc_cli::PurgeChainCmd),
+    /// Benchmark runtime pallets.
+    Benchmark(frame_benchmarking_cli::BenchmarkCmd),
 }
 
 impl SubstrateCli for Cli {
@@ -67,6 +69,18 @@ fn main() -> sc_cli::Result<()> {
             force_parity_db(&mut runner);
             runner.sync_run(|config| cmd.run(config.database))
         }
+        Some(Subcommand::Benchmark(cmd)) => {
+            if cfg!(feature = "runtime-benchmarks") {
+                let runner = cli.create_runner(cmd)?;
+                runner.sync_run(|config| {
+                    cmd.run::<test_runtime::opaque::Block, test_node::Executor>(config)
+                })
+            } else {
+                Err("Benchmarking wasn't enabled when building the node. \
+                     You can enable it with `--features runtime-benchmarks`."
+                    .into())
+            }
+        }
         None => {
             let mut runner = cli.create_runner(&cli.run)?;
             force_parity_db(&mut runner);
@@ -28,6 +28,7 @@ use sp_std::prelude::*;
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
+use sunshine_identity_pallet::Role;
 
 // A few exports that help ease life for downstream crates.
 pub use frame_support::{
@@ -257,11 +258,27 @@ impl pallet_transaction_payment::Trait for Runtime {
     type FeeMultiplierUpdate = ();
 }
 
+/// Lets identity teams own chains.
+pub struct IdentityTeams;
+
+impl sunshine_chain_pallet::Teams<AccountId> for IdentityTeams {
+    type Team = u32;
+
+    fn is_writer(team: u32, who: &AccountId) -> bool {
+        Identity::key_role(team, who) >= Some(Role::Writer)
+    }
+
+    fn is_admin(team: u32, who: &AccountId) -> bool {
+        Identity::key_role(team, who) >= Some(Role::Admin)
+    }
+}
+
 impl sunshine_chain_pallet::Trait for Runtime {
     type ChainId = u64;
     type Number = u64;
     type TrieHasher = sunshine_codec::hasher::TreeHasherBlake2b256;
     type TrieHash = sunshine_codec::hasher::TreeHashBlake2b256;
+    type Teams = IdentityTeams;
     type Event = Event;
 }
 
@@ -270,12 +287,29 @@ impl sunshine_faucet_pallet::Trait for Runtime {
     type Event = Event;
 }
 
+parameter_types! {
+    pub const AccountDeposit: Balance = 1_000_000;
+    pub const UsernameDeposit: Balance = 1_000_000;
+    pub const MaxPasswordMasks: u16 = 16;
+    pub const MaxKeys: u32 = 16;
+    pub const MaxTeams: u32 = 32;
+    pub const MaxMembers: u32 = 64;
+}
+
 impl sunshine_identity_pallet::Trait for Runtime {
     type Uid = u32;
     type Cid = sunshine_codec::Cid;
     type Mask = [u8; 32];
     type Gen = u16;
+    type MaxPasswordMasks = MaxPasswordMasks;
     type AccountData = pallet_balances::AccountData<Balance>;
+    type Currency = Balances;
+    type AccountDeposit = AccountDeposit;
+    type UsernameDeposit = UsernameDeposit;
+    type MaxKeys = MaxKeys;
+    type MaxTeams = MaxTeams;
+    type MaxMembers = MaxMembers;
+    type WeightInfo = sunshine_identity_pallet::weights::SubstrateWeight<Runtime>;
     type Event = Event;
 }
 
@@ -415,6 +449,24 @@ impl_runtime_apis! {
         }
     }
 
+    #[cfg(feature = "runtime-benchmarks")]
+    impl frame_benchmarking::Benchmark<Block> for Runtime {
+        fn dispatch_benchmark(
+            config: frame_benchmarking::BenchmarkConfig,
+        ) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
+            use frame_benchmarking::{add_benchmark, BenchmarkBatch, Benchmarking};
+
+            let whitelist: Vec<Vec<u8>> = vec![];
+            let mut batches = Vec::<BenchmarkBatch>::new();
+            let params = (&config, &whitelist);
+            add_benchmark!(params, batches, sunshine_identity_pallet, Identity);
+            if batches.is_empty() {
+                return Err("Benchmark not found for this pallet.".into());
+            }
+            Ok(batches)
+        }
+    }
+
     impl fg_primitives::GrandpaApi<Block> for Runtime {
         fn grandpa_authorities() -> GrandpaAuthorityList {
             Grandpa::grandpa_authorities()
@@ -15,3 +15,7 @@ pub struct AddAuthority;
 #[derive(Debug, Error)]
 #[error("Couldn't remove authority.")]
 pub struct RemoveAuthority;
+
+#[derive(Debug, Error)]
+#[error("Couldn't set team.")]
+pub struct SetTeam;
@@ -3,7 +3,7 @@ mod subxt;
 
 pub use subxt::*;
 
-use crate::error::{AddAuthority, AuthorBlock, CreateChain, RemoveAuthority};
+use crate::error::{AddAuthority, AuthorBlock, CreateChain, RemoveAuthority, SetTeam};
 use core::marker::PhantomData;
 use libipld::alias;
 use libipld::block::Block;
@@ -205,6 +205,15 @@ where
         chain_id: <N::Runtime as Chain>::ChainId,
         authority: &<N::Runtime as System>::AccountId,
     ) -> Result<<N::Runtime as Chain>::Number>;
+    async fn team(
+        &self,
+        chain_id: <N::Runtime as Chain>::ChainId,
+    ) -> Result<Option<<N::Runtime as Chain>::Team>>;
+    async fn set_team(
+        &self,
+        chain_id: <N::Runtime as Chain>::ChainId,
+        team: Option<<N::Runtime as Chain>::Team>,
+    ) -> Result<<N::Runtime as Chain>::Number>;
 }
 
 #[async_trait]
@@ -310,14 +319,37 @@ where
             .ok_or(RemoveAuthority)?
             .number)
     }
+
+    async fn team(
+        &self,
+        chain_id: <N::Runtime as Chain>::ChainId,
+    ) -> Result<Option<<N::Runtime as Chain>::Team>> {
+        Ok(self.chain_client().chain_team(chain_id, None).await?)
+    }
+
+    async fn set_team(
+        &self,
+        chain_id: <N::Runtime as Chain>::ChainId,
+        team: Option<<N::Runtime as Chain>::Team>,
+    ) -> Result<<N::Runtime as Chain>::Number> {
+        Ok(self
+            .chain_client()
+            .set_team_and_watch(&self.chain_signer()?, chain_id, team)
+            .await?
+            .team_changed()?
+            .ok_or(SetTeam)?
+            .number)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use async_std::prelude::*;
     use parity_scale_codec::{Decode, Encode};
+    use sunshine_client_utils::crypto::secrecy::SecretString;
     use test_client::chain::{Chain, ChainClient, ChainRootStoreExt};
     use test_client::client::{AccountKeyring, Client as _, Node as _};
+    use test_client::identity::{IdentityClient, Role};
     use test_client::{Client, Node, Runtime};
 
     #[derive(Clone, Debug, Eq, PartialEq, Decode, Encode)]
@@ -432,4 +464,30 @@ mod tests {
         ra.unwrap();
         rb.unwrap();
     }
+
+    #[async_std::test]
+    async fn test_team() {
+        env_logger::try_init().ok();
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Bob).await;
+        let bob = client2
+            .fetch_uid(&AccountKeyring::Bob.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        let password = SecretString::new("password2".to_string());
+        client1.change_password(&password).await.unwrap();
+        client1.set_encryption_key(&password).await.unwrap();
+
+        let team = client1.create_team().await.unwrap();
+        client1.add_member(team, bob, Role::Writer).await.unwrap();
+        let chain_id = client1.create_chain().await.unwrap();
+        assert!(client2.author_block(chain_id, &0u64).await.is_err());
+
+        client1.set_team(chain_id, Some(team)).await.unwrap();
+        assert_eq!(client2.team(chain_id).await.unwrap(), Some(team));
+        client2.author_block(chain_id, &0u64).await.unwrap();
+        assert!(client2.set_team(chain_id, None).await.is_err());
+    }
 }
@@ -39,6 +39,9 @@ pub trait Chain: System {
         + Encode
         + Ord
         + Into<u64>;
+
+    /// Team ID type.
+    type Team: Parameter + Member + Copy + Default;
 }
 
 #[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
@@ -47,6 +50,12 @@ pub struct AuthoritiesStore<T: Chain> {
     pub chain_id: T::ChainId,
 }
 
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct ChainTeamStore<T: Chain> {
+    #[store(returns = Option<T::Team>)]
+    pub chain_id: T::ChainId,
+}
+
 #[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
 pub struct ChainRootStore<T: Chain> {
     #[store(returns = Option<T::TrieHash>)]
@@ -76,6 +85,12 @@ pub struct RemoveAuthorityCall<'a, T: Chain> {
     pub authority: &'a <T as System>::AccountId,
 }
 
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct SetTeamCall<T: Chain> {
+    pub chain_id: T::ChainId,
+    pub team: Option<T::Team>,
+}
+
 #[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
 pub struct AuthorBlockCall<'a, T: Chain> {
     pub chain_id: T::ChainId,
@@ -111,3 +126,10 @@ pub struct AuthorityRemovedEvent<T: Chain> {
     pub who: <T as System>::AccountId,
     pub authority: <T as System>::AccountId,
 }
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct TeamChangedEvent<T: Chain> {
+    pub chain_id: T::ChainId,
+    pub number: T::Number,
+    pub who: <T as System>::AccountId,
+}
@@ -17,6 +17,32 @@ use sp_runtime::traits::{CheckedAdd, Member};
 use sp_std::prelude::*;
 use sp_trie::Layout;
 
+/// Teams that can own chains.
+pub trait Teams<AccountId> {
+    /// Team ID type.
+    type Team: Parameter + Member + Copy;
+
+    /// Returns `true` if `who` is a key of a team member that can author
+    /// blocks.
+    fn is_writer(team: Self::Team, who: &AccountId) -> bool;
+
+    /// Returns `true` if `who` is a key of a team member that can change
+    /// the authorities.
+    fn is_admin(team: Self::Team, who: &AccountId) -> bool;
+}
+
+impl<AccountId> Teams<AccountId> for () {
+    type Team = ();
+
+    fn is_writer(_: (), _: &AccountId) -> bool {
+        false
+    }
+
+    fn is_admin(_: (), _: &AccountId) -> bool {
+        false
+    }
+}
+
 /// The pallet's configuration trait.
 pub trait Trait: System {
     /// Chain ID type.
@@ -38,10 +64,15 @@ pub trait Trait: System {
         + Copy
         + core::hash::Hash;
 
+    /// Teams that can own chains.
+    type Teams: Teams<<Self as System>::AccountId>;
+
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as System>::Event>;
 }
 
+type TeamOf<T> = <<T as Trait>::Teams as Teams<<T as System>::AccountId>>::Team;
+
 decl_storage! {
     trait Store for Module<T: Trait> as ChainModule {
         ChainIdCounter: T::ChainId;
@@ -50,6 +81,10 @@ decl_storage! {
             hasher(blake2_128_concat) T::ChainId
             => OrderedSet<<T as System>::AccountId>;
 
+        pub ChainTeam get(fn team): map
+            hasher(blake2_128_concat) T::ChainId
+            => Option<TeamOf<T>>;
+
         pub ChainRoot get(fn chain_head): map
             hasher(blake2_128_concat) T::ChainId
             => Option<T::TrieHash>;
@@ -72,6 +107,7 @@ decl_event! {
         NewBlock(ChainId, Number, AccountId, TrieHash),
         AuthorityAdded(ChainId, Number, AccountId, AccountId),
         AuthorityRemoved(ChainId, Number, AccountId, AccountId),
+        TeamChanged(ChainId, Number, AccountId),
     }
 }
 
@@ -119,7 +155,7 @@ decl_module! {
             authority: <T as System>::AccountId,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            Self::ensure_authorized(chain_id, &who)?;
+            Self::ensure_admin(chain_id, &who)?;
             Self::add_authority_to_chain(chain_id, who, authority);
             Ok(())
         }
@@ -128,11 +164,31 @@ decl_module! {
         #[weight = 0]
         pub fn remove_authority(origin, chain_id: T::ChainId, authority: <T as System>::AccountId) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            Self::ensure_authorized(chain_id, &who)?;
+            Self::ensure_admin(chain_id, &who)?;
             Self::remove_authority_from_chain(chain_id, who, authority);
             Ok(())
         }
 
+        /// Set the team that owns the chain.
+        ///
+        /// Writers of the team can author blocks and admins of the team
+        /// can change the authorities and the team. Needs to be called by
+        /// an admin of the new team.
+        #[weight = 0]
+        pub fn set_team(origin, chain_id: T::ChainId, team: Option<TeamOf<T>>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_admin(chain_id, &who)?;
+            if let Some(team) = team {
+                if !T::Teams::is_admin(team, &who) {
+                    return Err(Error::<T>::Unauthorized.into());
+                }
+            }
+            <ChainTeam<T>>::mutate(chain_id, |prev| *prev = team);
+            let number = Self::height(chain_id);
+            Self::deposit_event(RawEvent::TeamChanged(chain_id, number, who));
+            Ok(())
+        }
+
         /// Author block.
         #[weight = 0]
         pub fn author_block(
@@ -176,7 +232,21 @@ impl<T: Trait> Module<T> {
         chain_id: T::ChainId,
         who: &<T as System>::AccountId,
     ) -> Result<(), Error<T>> {
-        if Self::is_authority(chain_id, who) {
+        let is_writer = <ChainTeam<T>>::get(chain_id)
+            .map(|team| T::Teams::is_writer(team, who))
+            .unwrap_or_default();
+        if Self::is_authority(chain_id, who) || is_writer {
+            Ok(())
+        } else {
+            Err(Error::<T>::Unauthorized)
+        }
+    }
+
+    fn ensure_admin(chain_id: T::ChainId, who: &<T as System>::AccountId) -> Result<(), Error<T>> {
+        let is_admin = <ChainTeam<T>>::get(chain_id)
+            .map(|team| T::Teams::is_admin(team, who))
+            .unwrap_or_default();
+        if Self::is_authority(chain_id, who) || is_admin {
             Ok(())
         } else {
             Err(Error::<T>::Unauthorized)
@@ -1,4 +1,4 @@
-use crate::{Module, Trait};
+use crate::{Module, Teams, Trait};
 use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
 use frame_system as system;
 use sp_core::H256;
@@ -48,11 +48,27 @@ impl system::Trait for Test {
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
 }
+/// Team 0 has the writer 2 and the admins 1 and 3.
+pub struct TestTeams;
+
+impl Teams<u64> for TestTeams {
+    type Team = u64;
+
+    fn is_writer(team: u64, who: &u64) -> bool {
+        team == 0 && (1..=3).contains(who)
+    }
+
+    fn is_admin(team: u64, who: &u64) -> bool {
+        team == 0 && (*who == 1 || *who == 3)
+    }
+}
+
 impl Trait for Test {
     type ChainId = u64;
     type Number = u64;
     type TrieHasher = sunshine_client_utils::codec::hasher::TreeHasherBlake2b256;
     type TrieHash = sunshine_client_utils::codec::hasher::TreeHashBlake2b256;
+    type Teams = TestTeams;
     type Event = ();
 }
 pub type ChainModule = Module<Test>;
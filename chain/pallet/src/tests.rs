@@ -45,3 +45,52 @@ fn test_block_authoring() {
         );
     });
 }
+
+#[test]
+fn test_team_authorities() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let writer = Origin::signed(2);
+        let admin = Origin::signed(3);
+        assert_ok!(ChainModule::create_chain(key1.clone()));
+        let chain_id = 0;
+
+        let block = Block {
+            number: 0,
+            ancestor: None,
+            payload: (),
+        }
+        .seal()
+        .unwrap();
+        let root = *block.offchain.root();
+        assert!(
+            ChainModule::author_block(writer.clone(), chain_id, root, block.proof.clone()).is_err()
+        );
+        assert!(ChainModule::set_team(admin.clone(), chain_id, Some(0)).is_err());
+        // Only admins of a team can hand the chain to it.
+        assert!(ChainModule::set_team(key1.clone(), chain_id, Some(1)).is_err());
+
+        assert_ok!(ChainModule::set_team(key1, chain_id, Some(0)));
+        assert_eq!(ChainModule::team(chain_id), Some(0));
+        assert!(ChainModule::add_authority(writer.clone(), chain_id, 4).is_err());
+        assert_ok!(ChainModule::add_authority(admin.clone(), chain_id, 4));
+        assert_ok!(ChainModule::author_block(
+            writer.clone(),
+            chain_id,
+            root,
+            block.proof.clone()
+        ));
+
+        assert_ok!(ChainModule::set_team(admin.clone(), chain_id, None));
+        assert!(ChainModule::set_team(admin, chain_id, Some(0)).is_err());
+        let block = Block {
+            number: 1,
+            ancestor: Some(root),
+            payload: (),
+        }
+        .seal()
+        .unwrap();
+        let root = *block.offchain.root();
+        assert!(ChainModule::author_block(writer, chain_id, root, block.proof).is_err());
+    });
+}
@@ -3,7 +3,7 @@ use substrate_subxt::sp_core::crypto::Ss58Codec;
 use substrate_subxt::system::System;
 use sunshine_cli_utils::client::crypto::ss58::Ss58;
 use sunshine_cli_utils::{ask_for_new_password, Node, Result};
-use sunshine_identity_client::{Identity, IdentityClient};
+use sunshine_identity_client::{resolve, Identifier, Identity, IdentityClient};
 
 #[derive(Clone, Debug, Clap)]
 pub struct AccountCreateCommand {
@@ -35,3 +35,84 @@ impl AccountPasswordCommand {
         Ok(())
     }
 }
+
+#[derive(Clone, Debug, Clap)]
+pub struct AccountCloseCommand {
+    pub beneficiary: String,
+}
+
+impl AccountCloseCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+        <N::Runtime as System>::AccountId: Ss58Codec,
+    {
+        let beneficiary: Ss58<N::Runtime> = self.beneficiary.parse()?;
+        if client.close_account(&beneficiary.0).await? {
+            println!("Your account was closed.");
+        } else {
+            println!("Confirm closing your account by running this command on another device.");
+        }
+        Ok(())
+    }
+}
+
+/// Merges account `from` into account `into`.
+///
+/// Both accounts need to confirm the merge. Claims can't be moved between
+/// claim chains, so at most one of the accounts may have identity claims.
+#[derive(Clone, Debug, Clap)]
+pub struct AccountMergeCommand {
+    pub from: String,
+    pub into: String,
+}
+
+impl AccountMergeCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+        <N::Runtime as System>::AccountId: Ss58Codec,
+    {
+        let from: Identifier<N::Runtime> = self.from.parse()?;
+        let into: Identifier<N::Runtime> = self.into.parse()?;
+        let from = resolve(client, Some(from)).await?;
+        let into = resolve(client, Some(into)).await?;
+        if client.merge_account(from, into).await? {
+            println!("Account {} was merged into {}.", from, into);
+        } else {
+            println!("Confirm the merge by running this command with a key of the other account.");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Clap)]
+pub struct AccountUsernameCommand {
+    pub username: Option<String>,
+
+    /// Clear the username and return the deposit.
+    #[clap(long = "clear")]
+    pub clear: bool,
+}
+
+impl AccountUsernameCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+        <N::Runtime as System>::AccountId: Ss58Codec,
+    {
+        if self.clear {
+            client.clear_username().await?;
+        } else if let Some(username) = &self.username {
+            client.set_username(username).await?;
+        } else {
+            let uid = resolve(client, None).await?;
+            if let Some(username) = client.fetch_username(uid).await? {
+                println!("Your username is @{}", username);
+            } else {
+                println!("You don't have a username");
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use clap::Clap;
 use substrate_subxt::sp_core::crypto::Ss58Codec;
 use substrate_subxt::system::System;
 use sunshine_cli_utils::client::crypto::ss58::Ss58;
-use sunshine_cli_utils::{Node, Result};
+use sunshine_cli_utils::{ask_for_password, Node, Result};
 use sunshine_identity_client::{resolve, Identifier, Identity, IdentityClient};
 
 #[derive(Clone, Debug, Clap)]
@@ -84,3 +84,18 @@ impl DevicePaperkeyCommand {
         Ok(())
     }
 }
+
+#[derive(Clone, Debug, Clap)]
+pub struct DeviceEncryptionKeyCommand;
+
+impl DeviceEncryptionKeyCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+    {
+        let password = ask_for_password("Please enter your password (8+ characters):\n", 8)?;
+        client.set_encryption_key(&password).await?;
+        println!("Published the encryption key of your device.");
+        Ok(())
+    }
+}
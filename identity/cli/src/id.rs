@@ -1,8 +1,14 @@
 use clap::Clap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
 use substrate_subxt::sp_core::crypto::Ss58Codec;
 use substrate_subxt::system::System;
-use sunshine_cli_utils::{Node, Result};
-use sunshine_identity_client::{resolve, Identifier, Identity, IdentityClient, Service};
+use sunshine_cli_utils::{ask_for_password, Node, Result};
+use sunshine_identity_client::{
+    resolve, EncryptedMessage, Identifier, Identity, IdentityClient, ProveOptions, Service,
+    UidSignature,
+};
 
 #[derive(Clone, Debug, Clap)]
 pub struct IdListCommand {
@@ -30,9 +36,80 @@ impl IdListCommand {
     }
 }
 
+#[derive(Clone, Debug, Clap)]
+pub struct IdAuditCommand {
+    pub identifier: Option<String>,
+}
+
+impl IdAuditCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+        <N::Runtime as System>::AccountId: Ss58Codec,
+    {
+        let identifier: Option<Identifier<N::Runtime>> = if let Some(identifier) = &self.identifier
+        {
+            Some(identifier.parse()?)
+        } else {
+            None
+        };
+        let uid = resolve(client, identifier).await?;
+        for report in client.verify_identity(uid).await? {
+            println!("{}", report);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Clap)]
+pub struct IdSshKeysCommand {
+    pub identifier: Option<String>,
+}
+
+impl IdSshKeysCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+        <N::Runtime as System>::AccountId: Ss58Codec,
+    {
+        let identifier: Option<Identifier<N::Runtime>> = if let Some(identifier) = &self.identifier
+        {
+            Some(identifier.parse()?)
+        } else {
+            None
+        };
+        let uid = resolve(client, identifier).await?;
+        for id in client.identity(uid).await? {
+            if let Some(key) = id.ssh_key() {
+                println!("{}", key);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a duration like `90d`, `12h`, `30m` or `45s`.
+fn parse_duration(s: &str) -> std::result::Result<Duration, String> {
+    let unit = match s.chars().last() {
+        Some('s') => 1,
+        Some('m') => 60,
+        Some('h') => 60 * 60,
+        Some('d') => 24 * 60 * 60,
+        Some('w') => 7 * 24 * 60 * 60,
+        _ => return Err(format!("invalid duration {}", s)),
+    };
+    let n: u64 = s[..s.len() - 1]
+        .parse()
+        .map_err(|_| format!("invalid duration {}", s))?;
+    Ok(Duration::from_secs(n * unit))
+}
+
 #[derive(Clone, Debug, Clap)]
 pub struct IdProveCommand {
     pub service: Service,
+    /// How long the claim is valid for, like `90d`.
+    #[clap(long = "expires", parse(try_from_str = parse_duration))]
+    pub expires: Option<Duration>,
 }
 
 impl IdProveCommand {
@@ -41,14 +118,55 @@ impl IdProveCommand {
         N::Runtime: Identity,
     {
         println!("Claiming {}...", self.service);
-        let instructions = self.service.cli_instructions();
-        let proof = client.prove_identity(self.service.clone()).await?;
+        let instructions = self.service.cli_instructions()?;
+        let options = ProveOptions {
+            expire_in: self.expires,
+        };
+        let proof = client
+            .prove_identity_with(self.service.clone(), options)
+            .await?;
         println!("{}", instructions);
         print!("{}", proof);
         Ok(())
     }
 }
 
+#[derive(Clone, Debug, Clap)]
+pub struct IdRenewCommand {
+    /// Renew claims that expire within this duration.
+    #[clap(long = "within", default_value = "14d", parse(try_from_str = parse_duration))]
+    pub within: Duration,
+    /// How long the renewed claims are valid for. Defaults to the validity
+    /// of the previous claim.
+    #[clap(long = "expires", parse(try_from_str = parse_duration))]
+    pub expires: Option<Duration>,
+}
+
+impl IdRenewCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+    {
+        let uid = resolve(client, None).await?;
+        for id in client.expiring_identities(uid, self.within).await? {
+            println!("{} is close to expiring ({})", id.service, id.status);
+            let previous = id
+                .ownership()
+                .map(|claim| claim.claim().expire_in)
+                .filter(|expire_in| *expire_in != u64::MAX)
+                .map(Duration::from_millis);
+            let options = ProveOptions {
+                expire_in: self.expires.or(previous),
+            };
+            let instructions = id.service.cli_instructions()?;
+            let proof = client.prove_identity_with(id.service, options).await?;
+            println!("{}", instructions);
+            print!("{}", proof);
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Clap)]
 pub struct IdRevokeCommand {
     pub service: Service,
@@ -63,3 +181,146 @@ impl IdRevokeCommand {
         Ok(())
     }
 }
+
+#[derive(Clone, Debug, Clap)]
+pub struct IdCheckpointCommand;
+
+impl IdCheckpointCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+    {
+        client.checkpoint().await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Clap)]
+pub struct IdAttachCommand {
+    pub service: Service,
+    /// File containing the public key that signed the proof.
+    pub key: PathBuf,
+    /// File containing the signed proof.
+    pub proof: PathBuf,
+}
+
+impl IdAttachCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+    {
+        let key = std::fs::read_to_string(&self.key)?;
+        let proof = std::fs::read_to_string(&self.proof)?;
+        client
+            .attach_proof(self.service.clone(), key, proof)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Clap)]
+pub struct IdTrackCommand {
+    pub identifier: String,
+}
+
+impl IdTrackCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+        <N::Runtime as System>::AccountId: Ss58Codec,
+    {
+        let identifier: Identifier<N::Runtime> = self.identifier.parse()?;
+        let uid = resolve(client, Some(identifier)).await?;
+        client.track(uid).await?;
+        println!("Tracking user id {}", uid);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Clap)]
+pub struct IdSignCommand {
+    /// File containing the message.
+    pub message: PathBuf,
+}
+
+impl IdSignCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+    {
+        let msg = std::fs::read(&self.message)?;
+        let envelope = client.sign_as_uid(&msg).await?;
+        println!("{}", envelope.encode()?);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Clap)]
+pub struct IdVerifyCommand {
+    /// File containing the message.
+    pub message: PathBuf,
+    /// The signature printed by `id sign`.
+    pub signature: String,
+}
+
+impl IdVerifyCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+    {
+        let msg = std::fs::read(&self.message)?;
+        let envelope = UidSignature::decode(&self.signature)?;
+        client.verify_uid_signature(&envelope, &msg).await?;
+        println!("Signed by user id {}", envelope.uid);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Clap)]
+pub struct IdEncryptCommand {
+    /// The user to encrypt the message to.
+    pub identifier: String,
+    /// File containing the message.
+    pub message: PathBuf,
+}
+
+impl IdEncryptCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+        <N::Runtime as System>::AccountId: Ss58Codec,
+    {
+        let identifier: Identifier<N::Runtime> = self.identifier.parse()?;
+        let uid = resolve(client, Some(identifier)).await?;
+        let msg = std::fs::read(&self.message)?;
+        let envelope = client.encrypt(uid, &msg).await?;
+        println!("{}", envelope.encode()?);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Clap)]
+pub struct IdDecryptCommand {
+    /// File containing the message printed by `id encrypt`.
+    pub message: PathBuf,
+    /// File to write the decrypted message to.
+    #[clap(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+impl IdDecryptCommand {
+    pub async fn exec<N: Node, C: IdentityClient<N>>(&self, client: &C) -> Result<()>
+    where
+        N::Runtime: Identity,
+    {
+        let envelope = EncryptedMessage::decode(&std::fs::read_to_string(&self.message)?)?;
+        let password = ask_for_password("Please enter your password (8+ characters):\n", 8)?;
+        let msg = client.decrypt(&password, &envelope).await?;
+        if let Some(output) = &self.output {
+            std::fs::write(output, msg)?;
+        } else {
+            std::io::stdout().write_all(&msg)?;
+        }
+        Ok(())
+    }
+}
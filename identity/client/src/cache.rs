@@ -0,0 +1,277 @@
+use crate::service::Service;
+use async_std::sync::Mutex;
+use libipld::cid::Cid;
+use libipld::DagCbor;
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// How long verification results are trusted before a proof is verified
+/// again.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CacheConfig {
+    /// Time to live of a proof that was found.
+    pub active_ttl: Duration,
+    /// Time to live of a proof that was not found.
+    pub missing_ttl: Duration,
+    /// How long an expired result is still used by lookups that tolerate
+    /// stale results.
+    pub max_stale: Duration,
+    /// Number of status changes that are kept.
+    pub max_changes: usize,
+    /// Number of verification results that are kept.
+    pub max_proofs: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            active_ttl: Duration::from_secs(24 * 60 * 60),
+            missing_ttl: Duration::from_secs(60 * 60),
+            max_stale: Duration::from_secs(7 * 24 * 60 * 60),
+            max_changes: 256,
+            max_proofs: 4096,
+        }
+    }
+}
+
+static CONFIG: Lazy<RwLock<CacheConfig>> = Lazy::new(Default::default);
+
+/// Sets the time to live of cached verification results.
+pub fn set_cache_config(config: CacheConfig) {
+    *CONFIG.write().unwrap() = config;
+}
+
+/// Returns the time to live of cached verification results.
+pub fn cache_config() -> CacheConfig {
+    *CONFIG.read().unwrap()
+}
+
+/// Serializes updates of the proof cache.
+pub(crate) static PROOF_CACHE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Milliseconds since the unix epoch.
+pub(crate) fn now() -> u64 {
+    UNIX_EPOCH.elapsed().unwrap().as_millis() as u64
+}
+
+/// The result of verifying the proof of a claim.
+#[derive(Clone, Debug, Eq, PartialEq, DagCbor)]
+pub struct CachedProof {
+    /// The uid that made the claim.
+    pub uid: u64,
+    /// The ownership claim.
+    pub claim: Cid,
+    /// The url of the proof if it was found.
+    pub url: Option<String>,
+    /// When the proof was verified.
+    pub verified_at: u64,
+}
+
+impl CachedProof {
+    fn expires_at(&self, config: &CacheConfig) -> u64 {
+        let ttl = if self.url.is_some() {
+            config.active_ttl
+        } else {
+            config.missing_ttl
+        };
+        self.verified_at.saturating_add(ttl.as_millis() as u64)
+    }
+}
+
+/// A change of the verification result of a claim.
+#[derive(Clone, Debug, Eq, PartialEq, DagCbor)]
+pub struct StatusChange {
+    /// The ownership claim.
+    pub claim: Cid,
+    /// The service the claim is for.
+    pub service: Service,
+    /// The previous url of the proof.
+    pub from: Option<String>,
+    /// The current url of the proof.
+    pub to: Option<String>,
+    /// When the change was detected.
+    pub at: u64,
+}
+
+/// Verification results keyed by uid and claim cid, persisted in the
+/// offchain store.
+#[derive(Clone, Debug, Default, Eq, PartialEq, DagCbor)]
+pub struct ProofCache {
+    pub proofs: Vec<CachedProof>,
+    pub changes: Vec<StatusChange>,
+}
+
+impl ProofCache {
+    /// Returns the verification result of a claim even if it expired.
+    pub fn cached(&self, uid: u64, claim: &Cid) -> Option<&CachedProof> {
+        self.proofs
+            .iter()
+            .find(|proof| proof.uid == uid && &proof.claim == claim)
+    }
+
+    /// Returns the verification result of a claim if it hasn't expired.
+    pub fn fresh(
+        &self,
+        uid: u64,
+        claim: &Cid,
+        now: u64,
+        config: &CacheConfig,
+    ) -> Option<&CachedProof> {
+        self.cached(uid, claim)
+            .filter(|proof| proof.expires_at(config) > now)
+    }
+
+    /// Returns the verification result of a claim if it expired less than
+    /// `max_stale` ago.
+    pub fn usable(
+        &self,
+        uid: u64,
+        claim: &Cid,
+        now: u64,
+        config: &CacheConfig,
+    ) -> Option<&CachedProof> {
+        self.cached(uid, claim).filter(|proof| {
+            proof
+                .expires_at(config)
+                .saturating_add(config.max_stale.as_millis() as u64)
+                > now
+        })
+    }
+
+    /// Returns the uids and claims with expired verification results.
+    pub fn stale(&self, now: u64, config: &CacheConfig) -> Vec<(u64, Cid)> {
+        self.proofs
+            .iter()
+            .filter(|proof| proof.expires_at(config) <= now)
+            .map(|proof| (proof.uid, proof.claim.clone()))
+            .collect()
+    }
+
+    /// Records the result of verifying a claim and returns the status
+    /// change if the result differs from the previous one. The oldest
+    /// results are dropped when there are more than `max_proofs`.
+    pub fn update(
+        &mut self,
+        uid: u64,
+        claim: Cid,
+        service: &Service,
+        url: Option<String>,
+        now: u64,
+        config: &CacheConfig,
+    ) -> Option<StatusChange> {
+        let entry = self
+            .proofs
+            .iter_mut()
+            .find(|proof| proof.uid == uid && proof.claim == claim);
+        let change = match entry {
+            Some(entry) => {
+                let change = if entry.url != url {
+                    Some(StatusChange {
+                        claim: claim.clone(),
+                        service: service.clone(),
+                        from: entry.url.clone(),
+                        to: url.clone(),
+                        at: now,
+                    })
+                } else {
+                    None
+                };
+                entry.url = url;
+                entry.verified_at = now;
+                change
+            }
+            None => {
+                self.proofs.push(CachedProof {
+                    uid,
+                    claim,
+                    url,
+                    verified_at: now,
+                });
+                if self.proofs.len() > config.max_proofs {
+                    self.proofs.sort_by_key(|proof| proof.verified_at);
+                    let excess = self.proofs.len() - config.max_proofs;
+                    self.proofs.drain(..excess);
+                }
+                None
+            }
+        };
+        if let Some(change) = &change {
+            self.changes.push(change.clone());
+            if self.changes.len() > config.max_changes {
+                let excess = self.changes.len() - config.max_changes;
+                self.changes.drain(..excess);
+            }
+        }
+        change
+    }
+
+    /// Removes the verification results of the claims of `uid` that
+    /// aren't in `claims`. Returns `true` if a result was removed.
+    pub fn retain(&mut self, uid: u64, claims: &[Cid]) -> bool {
+        let len = self.proofs.len();
+        self.proofs
+            .retain(|proof| proof.uid != uid || claims.contains(&proof.claim));
+        self.proofs.len() != len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cid() -> Cid {
+        "bafyreidogqfzz75tpkmjzjke425xqcrmpcib2p5tg44hnbirumdbpl5adu"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_proof_cache() {
+        let config = CacheConfig {
+            active_ttl: Duration::from_millis(100),
+            missing_ttl: Duration::from_millis(10),
+            max_stale: Duration::from_millis(50),
+            max_changes: 1,
+            max_proofs: 2,
+        };
+        let service = Service::new("github", "dvc94ch");
+        let mut cache = ProofCache::default();
+        let url = Some("https://gist".to_string());
+
+        assert!(cache.fresh(0, &cid(), 0, &config).is_none());
+        assert!(cache
+            .update(0, cid(), &service, url.clone(), 0, &config)
+            .is_none());
+        assert_eq!(cache.fresh(0, &cid(), 50, &config).unwrap().url, url);
+        assert!(cache.fresh(1, &cid(), 50, &config).is_none());
+        assert!(cache.fresh(0, &cid(), 100, &config).is_none());
+        assert_eq!(cache.cached(0, &cid()).unwrap().url, url);
+        assert!(cache.usable(0, &cid(), 120, &config).is_some());
+        assert!(cache.usable(0, &cid(), 150, &config).is_none());
+        assert_eq!(cache.stale(100, &config), vec![(0, cid())]);
+
+        let change = cache
+            .update(0, cid(), &service, None, 100, &config)
+            .unwrap();
+        assert_eq!(change.from, url);
+        assert_eq!(change.to, None);
+        assert!(cache.fresh(0, &cid(), 105, &config).is_some());
+        assert!(cache.fresh(0, &cid(), 110, &config).is_none());
+
+        cache.update(0, cid(), &service, url, 110, &config).unwrap();
+        assert_eq!(cache.changes.len(), 1);
+        assert_eq!(cache.changes[0].at, 110);
+
+        // the oldest results are dropped
+        cache.update(1, cid(), &service, None, 120, &config);
+        cache.update(2, cid(), &service, None, 130, &config);
+        assert_eq!(cache.proofs.len(), 2);
+        assert!(cache.fresh(0, &cid(), 120, &config).is_none());
+
+        assert!(!cache.retain(1, &[cid()]));
+        assert!(cache.retain(1, &[]));
+        assert_eq!(cache.proofs.len(), 1);
+        assert_eq!(cache.proofs[0].uid, 2);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::claim::{Claim, ClaimBody};
+use crate::service::Service;
+use libipld::cid::Cid;
+use std::collections::{HashMap, HashSet};
+
+/// Number of claims after which a checkpoint is published.
+pub(crate) const CHECKPOINT_INTERVAL: u32 = 64;
+
+/// Returns the sequence numbers of the claims that a checkpoint of `claims`
+/// summarizes.
+///
+/// For every service the latest ownership claim and the claims revoking or
+/// attaching a proof to it are kept, so resolving the identities from the
+/// summary gives the same status as walking all claims. Cross chain claims
+/// and the latest track claim of every uid are kept as well, as are the
+/// attestations that weren't revoked.
+pub(crate) fn compact(claims: &[Claim]) -> Vec<u32> {
+    let mut owned = HashMap::<&Service, u32>::new();
+    let mut tracked = HashMap::<u64, u32>::new();
+    let mut revoked = HashSet::new();
+    for claim in claims {
+        let seqno = claim.claim().seqno;
+        match &claim.claim().body {
+            ClaimBody::Ownership(service) => {
+                owned.insert(service, seqno);
+            }
+            ClaimBody::Revoke(seqno) => {
+                revoked.insert(*seqno);
+            }
+            ClaimBody::Track { uid, .. } => {
+                tracked.insert(*uid, seqno);
+            }
+            _ => {}
+        }
+    }
+    let latest: HashSet<u32> = owned.values().copied().collect();
+    claims
+        .iter()
+        .map(|claim| claim.claim())
+        .filter(|claim| match &claim.body {
+            ClaimBody::Ownership(_) => latest.contains(&claim.seqno),
+            ClaimBody::Revoke(seqno) | ClaimBody::Proof { seqno, .. } => latest.contains(seqno),
+            ClaimBody::CrossChain { .. } => true,
+            ClaimBody::Track { uid, .. } => tracked.get(uid) == Some(&claim.seqno),
+            ClaimBody::Attestation { .. } => !revoked.contains(&claim.seqno),
+            ClaimBody::Checkpoint(_) => false,
+        })
+        .map(|claim| claim.seqno)
+        .collect()
+}
+
+/// Returns the cids of the claims that a checkpoint of `claims` summarizes.
+pub(crate) fn summarize(claims: &[(Cid, Claim)]) -> Vec<Cid> {
+    let prefix: Vec<_> = claims.iter().map(|(_, claim)| claim.clone()).collect();
+    compact(&prefix)
+        .into_iter()
+        .filter_map(|seqno| {
+            claims
+                .iter()
+                .find(|(_, claim)| claim.claim().seqno == seqno)
+                .map(|(cid, _)| cid.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claim::UnsignedClaim;
+
+    fn claims(bodies: Vec<ClaimBody>) -> Vec<Claim> {
+        bodies
+            .into_iter()
+            .enumerate()
+            .map(|(i, body)| {
+                let claim = UnsignedClaim {
+                    genesis: vec![],
+                    block: vec![],
+                    uid: 42,
+                    public: String::new(),
+                    prev: None,
+                    seqno: i as u32 + 1,
+                    ctime: 0,
+                    expire_in: u64::MAX,
+                    body,
+                };
+                Claim::new(claim, vec![])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compact() {
+        let github = Service::new("github", "dvc94ch");
+        let dns = Service::new("dns", "example.com");
+        let cid = "bafyreidogqfzz75tpkmjzjke425xqcrmpcib2p5tg44hnbirumdbpl5adu"
+            .parse()
+            .unwrap();
+        let track = |uid| ClaimBody::Track {
+            uid,
+            snapshot_cid: cid.clone(),
+        };
+        let attestation = ClaimBody::Attestation {
+            topic: "release".into(),
+            data_cid: cid.clone(),
+        };
+        let claims = claims(vec![
+            ClaimBody::Ownership(github.clone()),
+            ClaimBody::Revoke(1),
+            ClaimBody::Ownership(dns),
+            track(7),
+            ClaimBody::Ownership(github),
+            ClaimBody::Proof {
+                seqno: 5,
+                key: String::new(),
+                proof: String::new(),
+            },
+            ClaimBody::Proof {
+                seqno: 1,
+                key: String::new(),
+                proof: String::new(),
+            },
+            ClaimBody::Checkpoint(vec![]),
+            track(7),
+            ClaimBody::Revoke(3),
+            attestation.clone(),
+            attestation,
+            ClaimBody::Revoke(11),
+        ]);
+        assert_eq!(compact(&claims), vec![3, 5, 6, 9, 10, 12]);
+    }
+}
@@ -56,8 +56,13 @@ pub struct UnsignedClaim {
 
 impl UnsignedClaim {
     pub fn expired(&self) -> bool {
+        self.expires_within(Duration::from_secs(0))
+    }
+
+    /// Returns `true` if the claim expires within `duration` from now.
+    pub fn expires_within(&self, duration: Duration) -> bool {
         let expires_at = Duration::from_millis(self.ctime.saturating_add(self.expire_in));
-        UNIX_EPOCH.elapsed().unwrap() > expires_at
+        UNIX_EPOCH.elapsed().unwrap() + duration > expires_at
     }
 
     pub fn to_bytes(&self) -> Result<Box<[u8]>> {
@@ -69,6 +74,117 @@ impl UnsignedClaim {
 pub enum ClaimBody {
     Ownership(Service),
     Revoke(u32),
+    /// Attaches a proof and the key that signed it to the ownership claim
+    /// with sequence number `seqno`.
+    Proof {
+        seqno: u32,
+        key: String,
+        proof: String,
+    },
+    /// Links the user to `uid` on the chain with `genesis`. The link is
+    /// signed by `public`, which was a key of `uid` at `block` on the other
+    /// chain.
+    CrossChain {
+        genesis: Vec<u8>,
+        uid: u64,
+        block: Vec<u8>,
+        public: String,
+        signature: Vec<u8>,
+    },
+    /// Tracks `uid` by pinning a snapshot of its verified proofs.
+    Track {
+        uid: u64,
+        snapshot_cid: Cid,
+    },
+    /// Signs an arbitrary statement, like the hash of a release artifact,
+    /// stored at `data_cid`.
+    Attestation {
+        topic: String,
+        data_cid: Cid,
+    },
+    /// Summarizes the claims that are still relevant, so the claims before
+    /// the checkpoint don't need to be verified.
+    Checkpoint(Vec<Cid>),
+}
+
+/// The statement that `uid` on the chain with `genesis` is the same user as
+/// `other_uid` on the chain with `other_genesis`.
+#[derive(Clone, Debug, Eq, PartialEq, DagCbor)]
+pub(crate) struct ChainLink {
+    pub genesis: Vec<u8>,
+    pub uid: u64,
+    pub other_genesis: Vec<u8>,
+    pub other_uid: u64,
+}
+
+impl ChainLink {
+    pub fn to_bytes(&self) -> Result<Box<[u8]>> {
+        Ok(DagCborCodec.encode(self)?.into_boxed_slice())
+    }
+}
+
+/// The result of verifying a single claim.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClaimStatus {
+    Valid,
+    Expired,
+    /// The claim failed to verify.
+    Invalid(String),
+    /// The claim couldn't be fetched from the offchain store.
+    Unavailable(String),
+}
+
+impl core::fmt::Display for ClaimStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Valid => write!(f, "valid"),
+            Self::Expired => write!(f, "expired"),
+            Self::Invalid(reason) => write!(f, "invalid: {}", reason),
+            Self::Unavailable(reason) => write!(f, "unavailable: {}", reason),
+        }
+    }
+}
+
+/// A claim in the claim chain of a user and the result of verifying it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimReport {
+    pub cid: Cid,
+    /// The claim, unless it was unavailable.
+    pub claim: Option<Claim>,
+    pub status: ClaimStatus,
+}
+
+impl ClaimReport {
+    /// Returns the key that signed the claim.
+    pub fn key(&self) -> Option<&str> {
+        self.claim
+            .as_ref()
+            .map(|claim| claim.claim().public.as_str())
+    }
+
+    /// Returns the block at which the signing key was valid.
+    pub fn block(&self) -> Option<&[u8]> {
+        self.claim.as_ref().map(|claim| &claim.claim().block[..])
+    }
+}
+
+impl core::fmt::Display for ClaimReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if let Some(claim) = &self.claim {
+            let claim = claim.claim();
+            write!(
+                f,
+                "{} {:?} {} key {} block 0x",
+                claim.seqno, claim.body, self.status, claim.public
+            )?;
+            for b in &claim.block {
+                write!(f, "{:02x}", b)?;
+            }
+            Ok(())
+        } else {
+            write!(f, "{} {}", self.cid, self.status)
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -95,6 +211,27 @@ pub struct IdentityInfo {
     pub service: Service,
     pub claims: Vec<Claim>,
     pub status: IdentityStatus,
+    /// The key that signed the attached proof, if it was verified.
+    pub key: Option<String>,
+}
+
+impl IdentityInfo {
+    /// Returns the latest ownership claim.
+    pub fn ownership(&self) -> Option<&Claim> {
+        self.claims
+            .iter()
+            .rev()
+            .find(|claim| matches!(claim.claim().body, ClaimBody::Ownership(_)))
+    }
+
+    /// Returns the verified ssh public key in `authorized_keys` format.
+    pub fn ssh_key(&self) -> Option<&str> {
+        if self.service.service() == "ssh" {
+            self.key.as_deref().map(str::trim)
+        } else {
+            None
+        }
+    }
 }
 
 impl core::fmt::Display for IdentityInfo {
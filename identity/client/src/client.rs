@@ -1,8 +1,24 @@
-use crate::claim::{Claim, ClaimBody, IdentityInfo, IdentityStatus, UnsignedClaim};
-use crate::error::{InvalidClaim, NoAccount, NoBlockHash, ResolveFailure, RuntimeInvalid};
+use crate::cache::{cache_config, now, ProofCache, StatusChange, PROOF_CACHE_LOCK};
+use crate::checkpoint::{summarize, CHECKPOINT_INTERVAL};
+use crate::claim::{
+    ChainLink, Claim, ClaimBody, ClaimReport, ClaimStatus, IdentityInfo, IdentityStatus,
+    UnsignedClaim,
+};
+use crate::encrypt::{encryption_key, open, seal, Device, EncryptedMessage};
+use crate::error::{
+    AttestationNotFound, IdentityConflict, InvalidClaim, InvalidSignature, InvalidUsername,
+    NoAccount, NoBlockHash, NoEncryptionKey, NoTeamKey, ProofNotFound, ResolveFailure,
+    RuntimeInvalid, TeamKeyGenOverflow,
+};
+use crate::event::IdentityEventSubscription;
+use crate::index::{ServiceIndex, SERVICE_INDEX_LOCK};
 use crate::keystore::{Keystore, Mask};
+use crate::message::{signed_message, UidSignature};
 use crate::service::Service;
 use crate::subxt::*;
+use crate::team::TeamKey;
+use crate::track::TrackSnapshot;
+use crate::{PasswordUpdate, ProveOptions};
 use codec::{Decode, Encode};
 use core::convert::TryInto;
 use libipld::alias;
@@ -14,7 +30,7 @@ use std::collections::HashMap;
 use std::time::Duration;
 use std::time::UNIX_EPOCH;
 use substrate_subxt::sp_core::crypto::{Pair, Ss58Codec};
-use substrate_subxt::sp_runtime::traits::{IdentifyAccount, SignedExtension, Verify};
+use substrate_subxt::sp_runtime::traits::{CheckedAdd, IdentifyAccount, SignedExtension, Verify};
 use substrate_subxt::system::System;
 use substrate_subxt::{EventSubscription, EventsDecoder, Runtime, SignedExtra};
 use sunshine_client_utils::crypto::{
@@ -24,8 +40,29 @@ use sunshine_client_utils::crypto::{
     signer::GenericSigner,
 };
 use sunshine_client_utils::{Client, Node, OffchainConfig, Result, Signer};
+use sunshine_identity_utils::{normalize_username, Role};
 
+/// Publishes a claim and a checkpoint every `CHECKPOINT_INTERVAL` claims.
 async fn set_identity<N, C>(client: &C, claim: Claim) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let seqno = claim.claim().seqno;
+    publish_claim(client, claim).await?;
+    if seqno % CHECKPOINT_INTERVAL == 0 {
+        checkpoint(client).await?;
+    }
+    Ok(())
+}
+
+async fn publish_claim<N, C>(client: &C, claim: Claim) -> Result<()>
 where
     N: Node,
     N::Runtime: Identity,
@@ -121,9 +158,21 @@ where
     <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
     <N::Runtime as System>::AccountId: Ss58Codec,
 {
-    if claim.claim().uid != uid.into() {
-        return Err(InvalidClaim("uid").into());
-    }
+    // Claims made by an account that was merged into `uid` are valid.
+    let claim_uid = if claim.claim().uid == uid.into() {
+        uid
+    } else {
+        let claim_uid: <N::Runtime as Identity>::Uid = claim
+            .claim()
+            .uid
+            .to_string()
+            .parse()
+            .map_err(|_| InvalidClaim("uid"))?;
+        if follow_redirect(client, claim_uid).await? != uid {
+            return Err(InvalidClaim("uid").into());
+        }
+        claim_uid
+    };
     if &claim.claim().genesis[..] != client.chain_client().genesis().as_ref() {
         return Err(InvalidClaim("genesis").into());
     }
@@ -136,7 +185,7 @@ where
         return Err(InvalidClaim("seqno").into());
     }
     let block = Decode::decode(&mut &claim.claim().block[..])?;
-    let keys = client.chain_client().keys(uid, Some(block)).await?;
+    let keys = client.chain_client().keys(claim_uid, Some(block)).await?;
     let key = keys
         .iter()
         .find(|k| k.to_ss58check() == claim.claim().public)
@@ -149,6 +198,61 @@ where
     Ok(())
 }
 
+/// Returns the claims of `uid`, oldest first, with their cids.
+///
+/// The walk stops at the latest checkpoint and returns the claims it
+/// summarizes in place of the claims before it. In audit mode all claims are
+/// walked and verified, and every checkpoint is checked against the claims
+/// before it.
+async fn fetch_claims<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid, audit: bool) -> Result<Vec<(Cid, Claim)>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let mut claims = vec![];
+    let mut next = fetch_identity(client, uid).await?;
+    while let Some(cid) = next {
+        let claim: Claim = client.offchain_client().get(&cid).await?;
+        next = claim.claim().prev;
+        verify_claim(client, uid, &claim).await?;
+        let summary = match &claim.claim().body {
+            ClaimBody::Checkpoint(summary) if !audit => Some(summary.clone()),
+            _ => None,
+        };
+        let seqno = claim.claim().seqno;
+        claims.push((cid, claim));
+        if let Some(summary) = summary {
+            for cid in summary.into_iter().rev() {
+                let claim: Claim = client.offchain_client().get(&cid).await?;
+                // A checkpoint can only summarize earlier claims of the uid.
+                if claim.claim().seqno >= seqno {
+                    return Err(InvalidClaim("checkpoint").into());
+                }
+                verify_claim(client, uid, &claim).await?;
+                claims.push((cid, claim));
+            }
+            break;
+        }
+    }
+    claims.reverse();
+    if audit {
+        for (i, (_, claim)) in claims.iter().enumerate() {
+            if let ClaimBody::Checkpoint(summary) = &claim.claim().body {
+                if summary != &summarize(&claims[..i]) {
+                    return Err(InvalidClaim("checkpoint").into());
+                }
+            }
+        }
+    }
+    Ok(claims)
+}
+
 pub async fn create_account_for<N, C>(client: &C, key: &<N::Runtime as System>::AccountId) -> Result<()>
 where
     N: Node,
@@ -208,15 +312,122 @@ where
     N::Runtime: Identity,
     <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
     C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, EncryptedMessage>,
+    <N::Runtime as System>::AccountId: Ss58Codec + AsRef<[u8]>,
 {
     client
         .chain_client()
         .remove_key_and_watch(&client.chain_signer()?, key)
         .await?
         .key_removed()?;
+    rotate_team_keys(client).await
+}
+
+pub async fn close_account<N, C>(client: &C, beneficiary: &<N::Runtime as System>::AccountId) -> Result<bool>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    let closed = client
+        .chain_client()
+        .close_account_and_watch(&client.chain_signer()?, beneficiary)
+        .await?
+        .account_closed()?
+        .is_some();
+    Ok(closed)
+}
+
+pub async fn merge_account<N, C>(
+    client: &C,
+    from: <N::Runtime as Identity>::Uid,
+    into: <N::Runtime as Identity>::Uid,
+) -> Result<bool>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    // Claim chains can't be combined, the chain refuses the merge.
+    if fetch_identity(client, from).await?.is_some()
+        && fetch_identity(client, into).await?.is_some()
+    {
+        return Err(IdentityConflict.into());
+    }
+    let keys = client.chain_client().keys(from, None).await?.len() as u32;
+    let teams = client.chain_client().teams(from, None).await?.len() as u32;
+    let merged = client
+        .chain_client()
+        .merge_account_and_watch(&client.chain_signer()?, from, into, keys, teams)
+        .await?
+        .account_merged()?
+        .is_some();
+    Ok(merged)
+}
+
+pub async fn set_username<N, C>(client: &C, username: &str) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    let username = normalize_username(username.as_bytes()).ok_or(InvalidUsername)?;
+    client
+        .chain_client()
+        .set_username_and_watch(&client.chain_signer()?, &username)
+        .await?
+        .username_set()?;
     Ok(())
 }
 
+pub async fn clear_username<N, C>(client: &C) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    client
+        .chain_client()
+        .clear_username_and_watch(&client.chain_signer()?)
+        .await?
+        .username_cleared()?;
+    Ok(())
+}
+
+pub async fn fetch_username<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Option<String>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    Ok(client
+        .chain_client()
+        .username(uid, None)
+        .await?
+        .map(|username| String::from_utf8_lossy(&username).into_owned()))
+}
+
+pub async fn resolve_username<N, C>(client: &C, username: &str) -> Result<<N::Runtime as Identity>::Uid>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    let username = normalize_username(username.as_bytes()).ok_or(InvalidUsername)?;
+    let uid = client
+        .chain_client()
+        .username_lookup(&username, None)
+        .await?
+        .ok_or(ResolveFailure)?;
+    follow_redirect(client, uid).await
+}
+
 pub async fn change_password<N, C, K>(client: &C, password: &SecretString) -> Result<()>
 where
     N: Node,
@@ -233,7 +444,7 @@ where
     Ok(())
 }
 
-pub async fn update_password<N, C, K>(client: &mut C) -> Result<()>
+pub async fn update_password<N, C, K>(client: &mut C) -> Result<PasswordUpdate>
 where
     N: Node,
     N::Runtime: Identity<Gen = u16, Mask = [u8; 32]>,
@@ -246,6 +457,20 @@ where
         .ok_or(NoAccount)?;
     let pgen = client.chain_client().password_gen(uid, None).await?;
     let gen = client.keystore().gen().await?;
+    if gen >= pgen {
+        return Ok(PasswordUpdate::UpToDate);
+    }
+    // Only a bounded number of masks is kept on chain, the oldest ones
+    // are pruned first.
+    if client
+        .chain_client()
+        .password_mask(uid, gen + 1, None)
+        .await?
+        .is_none()
+    {
+        log::warn!("Password history pruned: device needs to be provisioned again");
+        return Ok(PasswordUpdate::ReprovisionRequired);
+    }
     for g in gen..pgen {
         log::info!("Password change detected: reencrypting keystore");
         let mask = client
@@ -258,7 +483,7 @@ where
             .apply_mask(&Mask::new(mask), g + 1)
             .await?;
     }
-    Ok(())
+    Ok(PasswordUpdate::Updated)
 }
 
 pub async fn subscribe_password_changes<N, C>(client: &C) -> Result<EventSubscription<N::Runtime>>
@@ -276,6 +501,23 @@ where
     Ok(subscription)
 }
 
+pub async fn subscribe_identity_events<N, C>(
+    client: &C,
+    uid: Option<<N::Runtime as Identity>::Uid>,
+) -> Result<IdentityEventSubscription<N::Runtime>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    let subscription = client.chain_client().subscribe_events().await?;
+    let mut decoder = EventsDecoder::<N::Runtime>::new(client.chain_client().metadata().clone());
+    decoder.with_identity();
+    let subscription = EventSubscription::<N::Runtime>::new(subscription, decoder);
+    Ok(IdentityEventSubscription::new(subscription, uid))
+}
+
 pub async fn fetch_uid<N, C>(client: &C, key: &<N::Runtime as System>::AccountId) -> Result<Option<<N::Runtime as Identity>::Uid>>
 where
     N: Node,
@@ -283,7 +525,24 @@ where
     <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
     C: Client<N>,
 {
-    Ok(client.chain_client().uid_lookup(key, None).await?)
+    if let Some(uid) = client.chain_client().uid_lookup(key, None).await? {
+        Ok(Some(follow_redirect(client, uid).await?))
+    } else {
+        Ok(None)
+    }
+}
+
+pub async fn follow_redirect<N, C>(client: &C, mut uid: <N::Runtime as Identity>::Uid) -> Result<<N::Runtime as Identity>::Uid>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    while let Some(next) = client.chain_client().redirect(uid, None).await? {
+        uid = next;
+    }
+    Ok(uid)
 }
 
 pub async fn fetch_keys<N, C>(
@@ -314,7 +573,7 @@ where
     Ok(client.chain_client().account(uid, None).await?)
 }
 
-pub async fn prove_identity<N, C>(client: &C, service: Service) -> Result<String>
+pub async fn prove_identity_with<N, C>(client: &C, service: Service, options: ProveOptions) -> Result<String>
 where
     N: Node,
     N::Runtime: Identity,
@@ -322,11 +581,14 @@ where
     C: Client<N>,
     C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
     <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
 {
     let uid = fetch_uid(client, client.signer()?.account_id())
         .await?
         .ok_or(NoAccount)?;
-    let claim = create_claim(client, ClaimBody::Ownership(service.clone()), None, uid).await?;
+    let body = ClaimBody::Ownership(service.clone());
+    let claim = create_claim(client, body, options.expire_in, uid).await?;
     let proof = service.proof(&claim)?;
     set_identity(client, claim).await?;
     Ok(proof)
@@ -339,6 +601,7 @@ where
     <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
     C: Client<N>,
     C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
     <N::Runtime as System>::AccountId: Ss58Codec,
     <N::Runtime as Runtime>::Signature: Decode,
     <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
@@ -346,65 +609,144 @@ where
     let uid = fetch_uid(client, client.signer()?.account_id())
         .await?
         .ok_or(NoAccount)?;
-    let id = identity(client, uid)
+    let id = verified_identity(client, uid, false, false)
         .await?
         .into_iter()
         .find(|id| id.service == service && id.status != IdentityStatus::Revoked);
     if let Some(id) = id {
-        let seqno = id.claims.last().unwrap().claim().seqno;
+        let seqno = id.ownership().ok_or(ProofNotFound)?.claim().seqno;
         let claim = create_claim(client, ClaimBody::Revoke(seqno), None, uid).await?;
         set_identity(client, claim).await?;
     }
     Ok(())
 }
 
-pub async fn identity<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<IdentityInfo>>
+pub async fn attach_proof<N, C>(client: &C, service: Service, key: String, proof: String) -> Result<()>
 where
     N: Node,
     N::Runtime: Identity,
     <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
     C: Client<N>,
     C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
     <N::Runtime as System>::AccountId: Ss58Codec,
     <N::Runtime as Runtime>::Signature: Decode,
     <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
 {
-    let mut claims = vec![];
-    let mut next = fetch_identity(client, uid).await?;
-    while let Some(cid) = next {
-        let claim = client.offchain_client().get(&cid).await?;
-        next = claim.claim().prev;
-        verify_claim(client, uid, &claim).await?;
-        claims.push(claim);
+    let uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    let id = verified_identity(client, uid, false, false)
+        .await?
+        .into_iter()
+        .find(|id| id.service == service && id.status == IdentityStatus::ProofNotFound)
+        .ok_or(ProofNotFound)?;
+    let ownership = id.ownership().ok_or(ProofNotFound)?;
+    service.verify_attached(ownership, &key, &proof)?;
+    let seqno = ownership.claim().seqno;
+    let claim = create_claim(client, ClaimBody::Proof { seqno, key, proof }, None, uid).await?;
+    set_identity(client, claim).await
+}
+
+/// Returns the identities of `uid` with their verified status.
+///
+/// With `allow_stale` cached verification results are used for up to
+/// `max_stale` after they expired, so that only proofs without a recent
+/// result are fetched.
+async fn verified_identity<N, C>(
+    client: &C,
+    uid: <N::Runtime as Identity>::Uid,
+    audit: bool,
+    allow_stale: bool,
+) -> Result<Vec<IdentityInfo>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let claims = fetch_claims(client, uid, audit).await?;
+    identity_info(client, uid, claims, allow_stale).await
+}
+
+/// Returns the identities described by the claims of `uid`.
+async fn identity_info<N, C>(
+    client: &C,
+    uid: <N::Runtime as Identity>::Uid,
+    claims: Vec<(Cid, Claim)>,
+    allow_stale: bool,
+) -> Result<Vec<IdentityInfo>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+{
+    let mut cids = HashMap::new();
+    let mut seqnos = HashMap::new();
+    for (cid, claim) in &claims {
+        cids.insert(claim.claim().seqno, cid);
+        seqnos.insert(claim.claim().seqno, claim);
     }
     let mut ids = HashMap::<Service, Vec<Claim>>::new();
-    for claim in claims.iter().rev() {
+    for (_, claim) in &claims {
         match claim.claim().body.clone() {
             ClaimBody::Ownership(service) => {
                 ids.entry(service.clone()).or_default().push(claim.clone());
             }
             ClaimBody::Revoke(seqno) => {
-                let index = claims.len() - seqno as usize;
-                if let Some(claim2) = claims.get(index) {
+                if let Some(claim2) = seqnos.get(&seqno) {
+                    match &claim2.claim().body {
+                        ClaimBody::Ownership(service) => {
+                            ids.entry(service.clone()).or_default().push(claim.clone());
+                        }
+                        ClaimBody::Attestation { .. } => {}
+                        _ => {
+                            return Err(
+                                InvalidClaim("cannot revoke: claim is not revokable").into()
+                            );
+                        }
+                    }
+                } else {
+                    return Err(InvalidClaim("cannot revoke: claim not found").into());
+                }
+            }
+            ClaimBody::Proof { seqno, .. } => {
+                if let Some(claim2) = seqnos.get(&seqno) {
                     if let ClaimBody::Ownership(service) = &claim2.claim().body {
                         ids.entry(service.clone()).or_default().push(claim.clone());
                     } else {
-                        return Err(InvalidClaim("cannot revoke: claim is not revokable").into());
+                        return Err(InvalidClaim("cannot attach: claim is not provable").into());
                     }
                 } else {
-                    return Err(InvalidClaim("cannot revoke: claim not found").into());
+                    return Err(InvalidClaim("cannot attach: claim not found").into());
                 }
             }
+            ClaimBody::CrossChain { .. }
+            | ClaimBody::Track { .. }
+            | ClaimBody::Attestation { .. }
+            | ClaimBody::Checkpoint(_) => {}
         }
     }
 
+    let config = cache_config();
+    let cache = load_proof_cache(client).await?;
+    let mut verified = vec![];
+    let mut live = vec![];
     let mut info = vec![];
     for (service, claims) in ids.into_iter() {
         let mut status = IdentityStatus::ProofNotFound;
         let mut proof = None;
+        let mut attached = None;
         for claim in &claims {
             match &claim.claim().body {
                 ClaimBody::Ownership(_) => {
+                    attached = None;
                     if claim.claim().expired() {
                         status = IdentityStatus::Expired;
                     } else {
@@ -420,11 +762,47 @@ where
                         }
                     }
                 }
+                ClaimBody::Proof {
+                    seqno,
+                    key,
+                    proof: p,
+                } => {
+                    if let Some(own) = proof {
+                        if own.claim().seqno == *seqno {
+                            attached = Some((key, p));
+                        }
+                    }
+                }
+                _ => {}
             }
         }
+        let mut verified_key = None;
         if status == IdentityStatus::ProofNotFound {
             if let Some(proof) = proof {
-                if let Ok(proof_url) = service.verify(proof.signature()).await {
+                let proof_url = if let Some((key, p)) = attached {
+                    let proof_url = service.verify_attached(proof, key, p).ok();
+                    if proof_url.is_some() {
+                        verified_key = Some(key.clone());
+                    }
+                    proof_url
+                } else {
+                    let cid = cids[&proof.claim().seqno];
+                    live.push(cid.clone());
+                    let cached = if allow_stale {
+                        cache.usable(uid.into(), cid, now(), &config)
+                    } else {
+                        cache.fresh(uid.into(), cid, now(), &config)
+                    };
+                    match cached {
+                        Some(cached) => cached.url.clone(),
+                        None => {
+                            let url = service.verify(proof).await.ok();
+                            verified.push((cid.clone(), service.clone(), url.clone()));
+                            url
+                        }
+                    }
+                };
+                if let Some(proof_url) = proof_url {
                     status = IdentityStatus::Active(proof_url);
                 }
             }
@@ -433,12 +811,14 @@ where
             service,
             claims,
             status,
+            key: verified_key,
         });
     }
+    record_proofs(client, uid.into(), verified, &live).await?;
     Ok(info)
 }
 
-pub async fn resolve<N, C>(client: &C, service: &Service) -> Result<<N::Runtime as Identity>::Uid>
+pub async fn attest<N, C>(client: &C, topic: String, data_cid: Cid) -> Result<()>
 where
     N: Node,
     N::Runtime: Identity,
@@ -449,61 +829,1895 @@ where
     <N::Runtime as Runtime>::Signature: Decode,
     <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
 {
-    let uids = service.resolve().await?;
-    for uid in uids {
-        let uid = if let Ok(uid) = uid.parse() {
-            uid
-        } else {
-            continue;
-        };
-        for id in identity(client, uid).await? {
-            if &id.service == service {
-                if let IdentityStatus::Active(_) = &id.status {
-                    return Ok(uid);
-                }
-            }
-        }
+    if topic.is_empty() {
+        return Err(InvalidClaim("topic").into());
     }
-    Err(ResolveFailure.into())
+    let uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    let body = ClaimBody::Attestation { topic, data_cid };
+    let claim = create_claim(client, body, None, uid).await?;
+    set_identity(client, claim).await
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use test_client::client::{AccountKeyring, Client as _, Node as _};
-    use test_client::identity::{IdentityClient, IdentityStatus, Service};
-    use test_client::{Client, Node};
-
-    #[async_std::test]
-    async fn prove_identity() {
-        let node = Node::new_mock();
-        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
-        let account_id = AccountKeyring::Alice.to_account_id();
-        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
-        let service = Service::Github("dvc94ch".into());
+pub async fn revoke_attestation<N, C>(client: &C, topic: &str, data_cid: &Cid) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    let claims = fetch_claims(client, uid, false).await?;
+    let seqnos: Vec<_> = active_attestations(&claims, topic)
+        .into_iter()
+        .filter(|claim| match &claim.claim().body {
+            ClaimBody::Attestation { data_cid: cid, .. } => cid == data_cid,
+            _ => false,
+        })
+        .map(|claim| claim.claim().seqno)
+        .collect();
+    if seqnos.is_empty() {
+        return Err(AttestationNotFound.into());
+    }
+    for seqno in seqnos {
+        let claim = create_claim(client, ClaimBody::Revoke(seqno), None, uid).await?;
+        set_identity(client, claim).await?;
+    }
+    Ok(())
+}
 
-        let ids = client.identity(uid).await.unwrap();
-        assert_eq!(ids.len(), 0);
+pub async fn attestations<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid, topic: &str) -> Result<Vec<Claim>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let claims = fetch_claims(client, uid, false).await?;
+    Ok(active_attestations(&claims, topic))
+}
 
-        client.prove_identity(service.clone()).await.unwrap();
-        let ids = client.identity(uid).await.unwrap();
-        assert_eq!(ids.len(), 1);
-        assert_eq!(&ids[0].service, &service);
-        assert_eq!(ids[0].status, IdentityStatus::ProofNotFound);
+/// Returns the attestations of `topic` that weren't revoked or expired.
+fn active_attestations(claims: &[(Cid, Claim)], topic: &str) -> Vec<Claim> {
+    let revoked: Vec<_> = claims
+        .iter()
+        .filter_map(|(_, claim)| match &claim.claim().body {
+            ClaimBody::Revoke(seqno) => Some(*seqno),
+            _ => None,
+        })
+        .collect();
+    claims
+        .iter()
+        .map(|(_, claim)| claim)
+        .filter(|claim| match &claim.claim().body {
+            ClaimBody::Attestation { topic: t, .. } => t == topic,
+            _ => false,
+        })
+        .filter(|claim| !revoked.contains(&claim.claim().seqno) && !claim.claim().expired())
+        .cloned()
+        .collect()
+}
+
+pub async fn sign_as_uid<N, C>(client: &C, msg: &[u8]) -> Result<UidSignature>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+{
+    let uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    let block = client
+        .chain_client()
+        .block_hash(None)
+        .await?
+        .ok_or(NoBlockHash)?
+        .as_ref()
+        .to_vec();
+    let signature = client.signer()?.sign(&signed_message(msg));
+    Ok(UidSignature {
+        uid: uid.into(),
+        public: client.signer()?.account_id().to_ss58check(),
+        block,
+        signature: Encode::encode(&signature),
+    })
+}
+
+pub async fn verify_uid_signature<N, C>(client: &C, envelope: &UidSignature, msg: &[u8]) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let uid: <N::Runtime as Identity>::Uid = envelope
+        .uid
+        .to_string()
+        .parse()
+        .map_err(|_| InvalidSignature("uid"))?;
+    let block = Decode::decode(&mut &envelope.block[..])?;
+    let keys = client.chain_client().keys(uid, Some(block)).await?;
+    let key = keys
+        .iter()
+        .find(|k| k.to_ss58check() == envelope.public)
+        .ok_or(InvalidSignature("key"))?;
+    let signature: <N::Runtime as Runtime>::Signature =
+        Decode::decode(&mut &envelope.signature[..])?;
+    if !signature.verify(&signed_message(msg)[..], key) {
+        return Err(InvalidSignature("signature").into());
+    }
+    Ok(())
+}
+
+pub async fn create_team<N, C>(client: &C) -> Result<<N::Runtime as Identity>::Uid>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, EncryptedMessage>,
+    <N::Runtime as System>::AccountId: Ss58Codec + AsRef<[u8]>,
+{
+    // The first key generation is encrypted to the devices of the owner, so
+    // one of them needs to have published an encryption key.
+    let uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    if encryption_keys(client, uid)
+        .await?
+        .iter()
+        .all(|(_, key)| key.is_none())
+    {
+        return Err(NoEncryptionKey.into());
+    }
+    let team = client
+        .chain_client()
+        .create_team_and_watch(&client.chain_signer()?)
+        .await?
+        .team_created()?
+        .ok_or(RuntimeInvalid)?
+        .team;
+    rotate_team_key(client, team).await?;
+    Ok(team)
+}
+
+pub async fn add_member<N, C>(
+    client: &C,
+    team: <N::Runtime as Identity>::Uid,
+    member: <N::Runtime as Identity>::Uid,
+    role: Role,
+) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, EncryptedMessage>,
+    <N::Runtime as System>::AccountId: Ss58Codec + AsRef<[u8]>,
+{
+    let joined = !members(client, team)
+        .await?
+        .iter()
+        .any(|(uid, _)| *uid == member);
+    client
+        .chain_client()
+        .add_member_and_watch(&client.chain_signer()?, team, member, role)
+        .await?
+        .member_added()?;
+    // New members can only read the generations published after they joined.
+    // A key revoked by a member that left is rotated on the next write of an
+    // admin.
+    if joined || client.chain_client().team_key_revoked(team, None).await? {
+        rotate_team_key(client, team).await?;
+    }
+    Ok(())
+}
+
+pub async fn remove_member<N, C>(
+    client: &C,
+    team: <N::Runtime as Identity>::Uid,
+    member: <N::Runtime as Identity>::Uid,
+) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, EncryptedMessage>,
+    <N::Runtime as System>::AccountId: Ss58Codec + AsRef<[u8]>,
+{
+    client
+        .chain_client()
+        .remove_member_and_watch(&client.chain_signer()?, team, member)
+        .await?
+        .member_removed()?;
+    rotate_team_keys(client).await
+}
+
+pub async fn members<N, C>(
+    client: &C,
+    team: <N::Runtime as Identity>::Uid,
+) -> Result<Vec<(<N::Runtime as Identity>::Uid, Role)>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    Ok(client.chain_client().members(team, None).await?)
+}
+
+pub async fn team_key_gen<N, C>(
+    client: &C,
+    team: <N::Runtime as Identity>::Uid,
+) -> Result<<N::Runtime as Identity>::Gen>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    Ok(client.chain_client().team_key_gen(team, None).await?)
+}
+
+/// Publishes the next generation of the team key encrypted to the devices
+/// of all members that published an encryption key.
+pub async fn rotate_team_key<N, C>(
+    client: &C,
+    team: <N::Runtime as Identity>::Uid,
+) -> Result<<N::Runtime as Identity>::Gen>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, EncryptedMessage>,
+    <N::Runtime as System>::AccountId: Ss58Codec + AsRef<[u8]>,
+{
+    let gen = team_key_gen(client, team)
+        .await?
+        .checked_add(&1u16.into())
+        .ok_or(TeamKeyGenOverflow)?;
+    let mut keys = vec![];
+    for (member, _) in members(client, team).await? {
+        keys.extend(encryption_keys(client, member).await?);
+    }
+    let devices = devices::<N::Runtime>(keys)?;
+    let key = TeamKey::generate(team.into(), gen.into());
+    let envelope = seal(team.into(), &devices, key.as_bytes())?;
+    let cid = client.offchain_client().insert(envelope).await?;
+    client
+        .chain_client()
+        .set_team_key_and_watch(&client.chain_signer()?, team, gen, &cid.into())
+        .await?
+        .team_key_changed()?;
+    Ok(gen)
+}
+
+/// Rotates the revoked keys of the teams the user is a writer of.
+///
+/// Members that leave a team or close their account can't rotate the key
+/// themselves, so this needs to be called by the remaining members, for
+/// example by the `run` command of the cli.
+pub async fn rotate_team_keys<N, C>(client: &C) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, EncryptedMessage>,
+    <N::Runtime as System>::AccountId: Ss58Codec + AsRef<[u8]>,
+{
+    let uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    for team in client.chain_client().teams(uid, None).await? {
+        let role = members(client, team)
+            .await?
+            .into_iter()
+            .find(|(member, _)| *member == uid)
+            .map(|(_, role)| role);
+        if role >= Some(Role::Writer) && client.chain_client().team_key_revoked(team, None).await? {
+            rotate_team_key(client, team).await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn team_key<N, C, K>(
+    client: &C,
+    team: <N::Runtime as Identity>::Uid,
+    gen: <N::Runtime as Identity>::Gen,
+    password: &SecretString,
+) -> Result<TeamKey>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N, KeyType = K, Keystore = Keystore<K>>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, EncryptedMessage>,
+    K: KeyType,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+{
+    let cid: Cid = client
+        .chain_client()
+        .team_keys(team, gen, None)
+        .await?
+        .ok_or(NoTeamKey)?
+        .into();
+    let envelope: EncryptedMessage = client.offchain_client().get(&cid).await?;
+    let key = decrypt(client, password, &envelope).await?;
+    TeamKey::from_bytes(team.into(), gen.into(), &key)
+}
+
+pub async fn set_encryption_key<N, C, K>(client: &C, password: &SecretString) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N, KeyType = K, Keystore = Keystore<K>>,
+    K: KeyType,
+{
+    let key = client.keystore().unlock(password).await?;
+    let public = encryption_key(&key.to_raw_vec());
+    client
+        .chain_client()
+        .set_encryption_key_and_watch(&client.chain_signer()?, &public)
+        .await?
+        .encryption_key_set()?;
+    Ok(())
+}
+
+pub async fn encryption_keys<N, C>(
+    client: &C,
+    uid: <N::Runtime as Identity>::Uid,
+) -> Result<Vec<(<N::Runtime as System>::AccountId, Option<[u8; 32]>)>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    let mut keys = vec![];
+    for key in fetch_keys(client, uid, None).await? {
+        let encryption_key = client
+            .chain_client()
+            .encryption_keys(uid, &key, None)
+            .await?;
+        keys.push((key, encryption_key));
+    }
+    Ok(keys)
+}
+
+pub async fn encrypt<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid, msg: &[u8]) -> Result<EncryptedMessage>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    <N::Runtime as System>::AccountId: Ss58Codec + AsRef<[u8]>,
+{
+    let devices = devices::<N::Runtime>(encryption_keys(client, uid).await?)?;
+    seal(uid.into(), &devices, msg)
+}
+
+/// Returns the devices that published an encryption key. Devices without
+/// one are logged and can't read the message.
+fn devices<T>(keys: Vec<(<T as System>::AccountId, Option<[u8; 32]>)>) -> Result<Vec<Device>>
+where
+    T: Identity,
+    <T as System>::AccountId: Ss58Codec,
+{
+    let devices: Vec<_> = keys
+        .into_iter()
+        .filter_map(|(key, encryption_key)| {
+            let ss58 = key.to_ss58check();
+            if encryption_key.is_none() {
+                log::warn!("skipping device {} without encryption key", ss58);
+            }
+            Some(Device {
+                ss58,
+                encryption_key: encryption_key?,
+            })
+        })
+        .collect();
+    if devices.is_empty() {
+        return Err(NoEncryptionKey.into());
+    }
+    Ok(devices)
+}
+
+pub async fn decrypt<N, C, K>(client: &C, password: &SecretString, envelope: &EncryptedMessage) -> Result<Vec<u8>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N, KeyType = K, Keystore = Keystore<K>>,
+    K: KeyType,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+{
+    let device = client.signer()?.account_id().to_ss58check();
+    let key = client.keystore().unlock(password).await?;
+    open(envelope, &device, &key.to_raw_vec())
+}
+
+pub async fn sign_cross_chain<N, C>(client: &C, genesis: Vec<u8>, uid: u64) -> Result<ClaimBody>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+{
+    let own_uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    let own_genesis = client.chain_client().genesis().as_ref().to_vec();
+    let block = client
+        .chain_client()
+        .block_hash(None)
+        .await?
+        .ok_or(NoBlockHash)?
+        .as_ref()
+        .to_vec();
+    let link = ChainLink {
+        genesis: own_genesis.clone(),
+        uid: own_uid.into(),
+        other_genesis: genesis,
+        other_uid: uid,
+    };
+    let signature = client.signer()?.sign(&link.to_bytes()?);
+    Ok(ClaimBody::CrossChain {
+        genesis: own_genesis,
+        uid: own_uid.into(),
+        block,
+        public: client.signer()?.account_id().to_ss58check(),
+        signature: Encode::encode(&signature),
+    })
+}
+
+pub async fn prove_cross_chain<N, C>(client: &C, link: ClaimBody) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    if !matches!(link, ClaimBody::CrossChain { .. }) {
+        return Err(InvalidClaim("body").into());
+    }
+    let uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    let claim = create_claim(client, link, None, uid).await?;
+    set_identity(client, claim).await
+}
+
+pub async fn cross_chain_links<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<Claim>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    Ok(fetch_claims(client, uid, false)
+        .await?
+        .into_iter()
+        .map(|(_, claim)| claim)
+        .filter(|claim| matches!(claim.claim().body, ClaimBody::CrossChain { .. }))
+        .collect())
+}
+
+/// Verifies a cross chain claim published on the other chain against the
+/// state of this chain.
+///
+/// The signature of the link is checked against the keys the linked uid
+/// had at the stated block, and the linked uid needs to have recorded the
+/// reciprocal claim on this chain. The claim itself is verified by the
+/// chain it was published on.
+pub async fn verify_cross_chain<N, C>(client: &C, claim: &Claim) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let (genesis, uid, block, public, signature) = match &claim.claim().body {
+        ClaimBody::CrossChain {
+            genesis,
+            uid,
+            block,
+            public,
+            signature,
+        } => (genesis, *uid, block, public, signature),
+        _ => return Err(InvalidClaim("body").into()),
+    };
+    if &genesis[..] != client.chain_client().genesis().as_ref() {
+        return Err(InvalidClaim("genesis").into());
+    }
+    let link_uid: <N::Runtime as Identity>::Uid =
+        uid.to_string().parse().map_err(|_| InvalidClaim("uid"))?;
+    let block = Decode::decode(&mut &block[..])?;
+    let keys = client.chain_client().keys(link_uid, Some(block)).await?;
+    let key = keys
+        .iter()
+        .find(|k| &k.to_ss58check() == public)
+        .ok_or(InvalidClaim("key"))?;
+    let link = ChainLink {
+        genesis: genesis.clone(),
+        uid,
+        other_genesis: claim.claim().genesis.clone(),
+        other_uid: claim.claim().uid,
+    };
+    let signature: <N::Runtime as Runtime>::Signature = Decode::decode(&mut &signature[..])?;
+    if !signature.verify(&link.to_bytes()?[..], key) {
+        return Err(InvalidClaim("signature").into());
+    }
+    let reciprocal = fetch_claims(client, follow_redirect(client, link_uid).await?, false)
+        .await?
+        .into_iter()
+        .any(|(_, own)| match &own.claim().body {
+            ClaimBody::CrossChain {
+                genesis: other_genesis,
+                uid: other_uid,
+                ..
+            } => other_genesis == &claim.claim().genesis && *other_uid == claim.claim().uid,
+            _ => false,
+        });
+    if !reciprocal {
+        return Err(InvalidClaim("reciprocal").into());
+    }
+    Ok(())
+}
+
+pub async fn checkpoint<N, C>(client: &C) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    let summary = summarize(&fetch_claims(client, uid, false).await?);
+    let claim = create_claim(client, ClaimBody::Checkpoint(summary), None, uid).await?;
+    publish_claim(client, claim).await
+}
+
+pub async fn audit_identity<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<IdentityInfo>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    verified_identity(client, uid, true, false).await
+}
+
+pub async fn verify_identity<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<ClaimReport>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    claim_reports(client, uid, true).await
+}
+
+/// Returns the claims summarized by the checkpoint `seqno` of `uid`.
+async fn checkpoint_claims<N, C>(
+    client: &C,
+    uid: <N::Runtime as Identity>::Uid,
+    seqno: u32,
+    summary: &[Cid],
+) -> Result<Vec<(Cid, Claim)>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let mut claims = vec![];
+    for cid in summary {
+        let claim: Claim = client.offchain_client().get(cid).await?;
+        // A checkpoint can only summarize earlier claims of the uid.
+        if claim.claim().seqno >= seqno {
+            return Err(InvalidClaim("checkpoint").into());
+        }
+        verify_claim(client, uid, &claim).await?;
+        claims.push((cid.clone(), claim));
+    }
+    Ok(claims)
+}
+
+/// Returns a report of the claims of `uid`, oldest first.
+///
+/// The walk stops at the latest valid checkpoint and reports the claims it
+/// summarizes in place of the claims before it. A checkpoint that
+/// summarizes a claim that doesn't verify is reported as invalid and the
+/// walk continues past it. In audit mode all claims are walked, and every
+/// checkpoint is checked against the claims before it.
+async fn claim_reports<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid, audit: bool) -> Result<Vec<ClaimReport>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let mut reports = vec![];
+    let mut next = fetch_identity(client, uid).await?;
+    while let Some(cid) = next {
+        let claim: Claim = match client.offchain_client().get(&cid).await {
+            Ok(claim) => claim,
+            Err(err) => {
+                reports.push(ClaimReport {
+                    cid,
+                    claim: None,
+                    status: ClaimStatus::Unavailable(err.to_string()),
+                });
+                break;
+            }
+        };
+        next = claim.claim().prev;
+        let mut status = match verify_claim(client, uid, &claim).await {
+            Ok(()) if claim.claim().expired() => ClaimStatus::Expired,
+            Ok(()) => ClaimStatus::Valid,
+            Err(err) => ClaimStatus::Invalid(err.to_string()),
+        };
+        let mut summarized = None;
+        if let ClaimBody::Checkpoint(summary) = &claim.claim().body {
+            if !audit && status == ClaimStatus::Valid {
+                match checkpoint_claims(client, uid, claim.claim().seqno, summary).await {
+                    Ok(claims) => summarized = Some(claims),
+                    Err(err) => status = ClaimStatus::Invalid(err.to_string()),
+                }
+            }
+        }
+        reports.push(ClaimReport {
+            cid,
+            claim: Some(claim),
+            status,
+        });
+        if let Some(claims) = summarized {
+            for (cid, claim) in claims.into_iter().rev() {
+                let status = if claim.claim().expired() {
+                    ClaimStatus::Expired
+                } else {
+                    ClaimStatus::Valid
+                };
+                reports.push(ClaimReport {
+                    cid,
+                    claim: Some(claim),
+                    status,
+                });
+            }
+            break;
+        }
+    }
+    reports.reverse();
+
+    // Check the claims that refer to other claims.
+    let mut claims: Vec<(Cid, Claim)> = vec![];
+    for report in &mut reports {
+        let claim = if let Some(claim) = &report.claim {
+            claim
+        } else {
+            continue;
+        };
+        let target = |seqno: &u32| {
+            claims
+                .iter()
+                .find(|(_, claim)| claim.claim().seqno == *seqno)
+                .map(|(_, claim)| &claim.claim().body)
+        };
+        let reason = match &claim.claim().body {
+            ClaimBody::Revoke(seqno) => match target(seqno) {
+                Some(ClaimBody::Ownership(_)) | Some(ClaimBody::Attestation { .. }) => None,
+                Some(_) => Some("cannot revoke: claim is not revokable"),
+                None => Some("cannot revoke: claim not found"),
+            },
+            ClaimBody::Proof { seqno, .. } => match target(seqno) {
+                Some(ClaimBody::Ownership(_)) => None,
+                Some(_) => Some("cannot attach: claim is not provable"),
+                None => Some("cannot attach: claim not found"),
+            },
+            ClaimBody::Checkpoint(summary) if audit && summary != &summarize(&claims) => {
+                Some("checkpoint doesn't match the claims before it")
+            }
+            _ => None,
+        };
+        claims.push((report.cid.clone(), claim.clone()));
+        if let Some(reason) = reason {
+            if report.status == ClaimStatus::Valid || report.status == ClaimStatus::Expired {
+                report.status = ClaimStatus::Invalid(InvalidClaim(reason).to_string());
+            }
+        }
+    }
+    Ok(reports)
+}
+
+/// Returns the claims of `uid` that verified, starting from the latest
+/// valid checkpoint. Invalid and unavailable claims are skipped together
+/// with the claims that refer to them.
+async fn valid_claims<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<(Cid, Claim)>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let mut claims: Vec<(Cid, Claim)> = vec![];
+    for report in claim_reports(client, uid, false).await? {
+        match (report.status, report.claim) {
+            (ClaimStatus::Valid, Some(claim)) | (ClaimStatus::Expired, Some(claim)) => {
+                let target = match &claim.claim().body {
+                    ClaimBody::Revoke(seqno) | ClaimBody::Proof { seqno, .. } => Some(*seqno),
+                    _ => None,
+                };
+                if let Some(target) = target {
+                    if !claims
+                        .iter()
+                        .any(|(_, claim)| claim.claim().seqno == target)
+                    {
+                        log::warn!(
+                            "skipping claim {} of uid {}: refers to a skipped claim",
+                            report.cid,
+                            uid
+                        );
+                        continue;
+                    }
+                }
+                claims.push((report.cid, claim));
+            }
+            (status, _) => log::warn!("skipping claim {} of uid {}: {}", report.cid, uid, status),
+        }
+    }
+    Ok(claims)
+}
+
+pub async fn expiring_identities<N, C>(
+    client: &C,
+    uid: <N::Runtime as Identity>::Uid,
+    within: Duration,
+) -> Result<Vec<IdentityInfo>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    Ok(verified_identity(client, uid, false, false)
+        .await?
+        .into_iter()
+        .filter(|id| id.status != IdentityStatus::Revoked)
+        .filter(|id| match id.ownership() {
+            Some(claim) => claim.claim().expires_within(within),
+            None => false,
+        })
+        .collect())
+}
+
+pub async fn identity<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<IdentityInfo>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, TrackSnapshot>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let claims = valid_claims(client, uid).await?;
+    let info = identity_info(client, uid, claims, false).await?;
+    if let Some(snapshot) = tracked_snapshot(client, uid).await? {
+        for service in snapshot.changed(&info) {
+            log::warn!("tracked proof of {} by uid {} changed", service, uid);
+        }
+    }
+    Ok(info)
+}
+
+pub async fn track<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, TrackSnapshot>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let own_uid = fetch_uid(client, client.signer()?.account_id())
+        .await?
+        .ok_or(NoAccount)?;
+    let ids = verified_identity(client, uid, false, false).await?;
+    let snapshot = TrackSnapshot::new(uid.into(), &ids);
+    let snapshot_cid = client.offchain_client().insert(snapshot).await?;
+    let body = ClaimBody::Track {
+        uid: uid.into(),
+        snapshot_cid,
+    };
+    let claim = create_claim(client, body, None, own_uid).await?;
+    set_identity(client, claim).await
+}
+
+pub async fn tracked_changes<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<Service>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, TrackSnapshot>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    if let Some(snapshot) = tracked_snapshot(client, uid).await? {
+        Ok(snapshot.changed(&verified_identity(client, uid, false, false).await?))
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Returns the latest snapshot of `uid` tracked by the user of the client.
+async fn tracked_snapshot<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Option<TrackSnapshot>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, TrackSnapshot>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let own_uid = match client.signer() {
+        Ok(signer) => fetch_uid(client, signer.account_id()).await?,
+        Err(_) => None,
+    };
+    let own_uid = match own_uid {
+        Some(own_uid) if own_uid != uid => own_uid,
+        _ => return Ok(None),
+    };
+    for (_, claim) in fetch_claims(client, own_uid, false).await?.iter().rev() {
+        if let ClaimBody::Track {
+            uid: tracked,
+            snapshot_cid,
+        } = &claim.claim().body
+        {
+            if *tracked == uid.into() {
+                return Ok(Some(client.offchain_client().get(snapshot_cid).await?));
+            }
+        }
+    }
+    Ok(None)
+}
+
+async fn load_proof_cache<N, C>(client: &C) -> Result<ProofCache>
+where
+    N: Node,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+{
+    let alias = alias!(sunshine_identity_proof_cache);
+    if let Some(root) = client.offchain_client().resolve(alias).await? {
+        Ok(client.offchain_client().get(&root).await?)
+    } else {
+        Ok(ProofCache::default())
+    }
+}
+
+async fn store_proof_cache<N, C>(client: &C, cache: ProofCache) -> Result<()>
+where
+    N: Node,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+{
+    let alias = alias!(sunshine_identity_proof_cache);
+    let root = client.offchain_client().insert(cache).await?;
+    client.offchain_client().alias(alias, Some(&root)).await?;
+    Ok(())
+}
+
+/// Records the verification results of the claims of `uid` and removes
+/// the results of its claims that were revoked, expired or replaced by a
+/// newer claim.
+async fn record_proofs<N, C>(
+    client: &C,
+    uid: u64,
+    verified: Vec<(Cid, Service, Option<String>)>,
+    live: &[Cid],
+) -> Result<()>
+where
+    N: Node,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+{
+    let config = cache_config();
+    let _guard = PROOF_CACHE_LOCK.lock().await;
+    let mut cache = load_proof_cache(client).await?;
+    let mut changed = cache.retain(uid, live);
+    for (cid, service, url) in verified {
+        cache.update(uid, cid, &service, url, now(), &config);
+        changed = true;
+    }
+    if changed {
+        store_proof_cache(client, cache).await?;
+    }
+    Ok(())
+}
+
+pub async fn reverify_proofs<N, C>(client: &C) -> Result<Vec<StatusChange>>
+where
+    N: Node,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+{
+    let config = cache_config();
+    let stale = load_proof_cache(client).await?.stale(now(), &config);
+    let mut results = Vec::with_capacity(stale.len());
+    for (uid, cid) in stale {
+        let claim: Claim = client.offchain_client().get(&cid).await?;
+        if let ClaimBody::Ownership(service) = &claim.claim().body {
+            let url = service.verify(&claim).await.ok();
+            results.push((uid, cid, service.clone(), url));
+        }
+    }
+    let _guard = PROOF_CACHE_LOCK.lock().await;
+    let mut cache = load_proof_cache(client).await?;
+    let changes = results
+        .into_iter()
+        .filter_map(|(uid, cid, service, url)| {
+            cache.update(uid, cid, &service, url, now(), &config)
+        })
+        .collect();
+    store_proof_cache(client, cache).await?;
+    Ok(changes)
+}
+
+pub async fn proof_status_changes<N, C>(client: &C) -> Result<Vec<StatusChange>>
+where
+    N: Node,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+{
+    Ok(load_proof_cache(client).await?.changes)
+}
+
+async fn load_service_index<N, C>(client: &C) -> Result<ServiceIndex>
+where
+    N: Node,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ServiceIndex>,
+{
+    let alias = alias!(sunshine_identity_service_index);
+    if let Some(root) = client.offchain_client().resolve(alias).await? {
+        Ok(client.offchain_client().get(&root).await?)
+    } else {
+        Ok(ServiceIndex::default())
+    }
+}
+
+async fn store_service_index<N, C>(client: &C, index: ServiceIndex) -> Result<()>
+where
+    N: Node,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ServiceIndex>,
+{
+    let alias = alias!(sunshine_identity_service_index);
+    let root = client.offchain_client().insert(index).await?;
+    client.offchain_client().alias(alias, Some(&root)).await?;
+    Ok(())
+}
+
+/// Returns the services with an ownership claim that wasn't revoked or
+/// expired, without verifying their proofs.
+async fn claimed_services<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<Service>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let claims = fetch_claims(client, uid, false).await?;
+    let mut owned = HashMap::new();
+    let mut active = HashMap::new();
+    for (_, claim) in &claims {
+        let seqno = claim.claim().seqno;
+        match &claim.claim().body {
+            ClaimBody::Ownership(service) => {
+                owned.insert(seqno, service);
+                if claim.claim().expired() {
+                    active.remove(service);
+                } else {
+                    active.insert(service, seqno);
+                }
+            }
+            ClaimBody::Revoke(seqno) => {
+                if let Some(service) = owned.get(seqno) {
+                    if active.get(service) == Some(seqno) {
+                        active.remove(service);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(active.keys().map(|service| (*service).clone()).collect())
+}
+
+pub async fn index_identity<N, C>(client: &C, uid: <N::Runtime as Identity>::Uid) -> Result<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ServiceIndex>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let services = claimed_services(client, uid).await?;
+    let _guard = SERVICE_INDEX_LOCK.lock().await;
+    let mut index = load_service_index(client).await?;
+    if index.set_claims(uid.into(), &services) {
+        store_service_index(client, index).await?;
+    }
+    Ok(())
+}
+
+pub async fn subscribe_identity_changes<N, C>(client: &C) -> Result<EventSubscription<N::Runtime>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+{
+    let subscription = client.chain_client().subscribe_events().await?;
+    let mut decoder = EventsDecoder::<N::Runtime>::new(client.chain_client().metadata().clone());
+    decoder.with_identity();
+    let mut subscription = EventSubscription::<N::Runtime>::new(subscription, decoder);
+    subscription.filter_event::<IdentityChangedEvent<_>>();
+    Ok(subscription)
+}
+
+/// Returns the uid if it has an active proof of the service.
+///
+/// Uids from the index are checked with `allow_stale`, so that lookups
+/// don't depend on the service being reachable. Results that expired more
+/// than `max_stale` ago are verified again.
+async fn active_claimant<N, C>(
+    client: &C,
+    service: &Service,
+    uid: &str,
+    allow_stale: bool,
+) -> Result<Option<<N::Runtime as Identity>::Uid>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let uid = if let Ok(uid) = uid.parse() {
+        follow_redirect(client, uid).await?
+    } else {
+        return Ok(None);
+    };
+    for id in verified_identity(client, uid, false, allow_stale).await? {
+        if &id.service == service {
+            if let IdentityStatus::Active(_) = &id.status {
+                return Ok(Some(uid));
+            }
+        }
+    }
+    Ok(None)
+}
+
+pub async fn resolve<N, C>(client: &C, service: &Service) -> Result<<N::Runtime as Identity>::Uid>
+where
+    N: Node,
+    N::Runtime: Identity,
+    <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+    C: Client<N>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ServiceIndex>,
+    <N::Runtime as System>::AccountId: Ss58Codec,
+    <N::Runtime as Runtime>::Signature: Decode,
+    <<N::Runtime as Runtime>::Signature as Verify>::Signer: IdentifyAccount<AccountId = <N::Runtime as System>::AccountId>,
+{
+    let indexed: Vec<String> = load_service_index(client)
+        .await?
+        .uids(service)
+        .iter()
+        .map(|uid| uid.to_string())
+        .collect();
+    for uid in indexed {
+        if let Some(uid) = active_claimant(client, service, &uid, true).await? {
+            return Ok(uid);
+        }
+    }
+    for uid in service.resolve().await? {
+        if let Some(uid) = active_claimant(client, service, &uid, false).await? {
+            let _guard = SERVICE_INDEX_LOCK.lock().await;
+            let mut index = load_service_index(client).await?;
+            if index.insert(service, uid.into()) {
+                store_service_index(client, index).await?;
+            }
+            return Ok(uid);
+        }
+    }
+    Err(ResolveFailure.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::tests::serve;
+    use async_std::sync::Mutex;
+    use once_cell::sync::Lazy;
+    use sunshine_client_utils::async_trait;
+    use test_client::client::{AccountKeyring, Client as _, Node as _};
+    use test_client::identity::{
+        register_service, set_cache_config, CacheConfig, Claim, ClaimBody, ClaimStatus,
+        EncryptedMessage, HttpsService, IdentityClient, IdentityEvent, IdentityStatus,
+        IdentityStoreExt, PasswordUpdate, ProofParams, ProofService, ProveOptions, Role, Service,
+        SetIdentityCallExt, UidSignature,
+    };
+    use test_client::{Client, Node};
+
+    /// Serializes the tests that register proof services or change the
+    /// cache config, which are global to the process.
+    static GLOBALS: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+    /// A service whose attached proofs are the key and the claim signature.
+    struct AttachedService;
+
+    #[async_trait]
+    impl ProofService for AttachedService {
+        fn name(&self) -> &str {
+            "attached"
+        }
+
+        async fn verify(&self, _username: &str, _claim: &Claim) -> Result<String> {
+            Err(ProofNotFound.into())
+        }
+
+        async fn resolve(&self, _username: &str) -> Result<Vec<String>> {
+            Ok(Vec::new())
+        }
+
+        fn verify_attached(
+            &self,
+            username: &str,
+            signature: &str,
+            key: &str,
+            proof: &str,
+        ) -> Result<String> {
+            if key != username || proof != format!("{}:{}", key, signature) {
+                return Err(ProofNotFound.into());
+            }
+            Ok(format!("attached:{}", key))
+        }
+
+        fn proof(&self, params: &ProofParams<'_>) -> String {
+            params.signature.clone()
+        }
+
+        fn cli_instructions(&self, _username: &str) -> String {
+            String::new()
+        }
+    }
+
+    #[async_std::test]
+    async fn prove_identity() {
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
+        let service = Service::new("github", "dvc94ch");
+
+        let ids = client.identity(uid).await.unwrap();
+        assert_eq!(ids.len(), 0);
+
+        client.prove_identity(service.clone()).await.unwrap();
+        let ids = client.identity(uid).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(&ids[0].service, &service);
+        assert_eq!(ids[0].status, IdentityStatus::ProofNotFound);
 
         client.revoke_identity(service.clone()).await.unwrap();
         let ids = client.identity(uid).await.unwrap();
         assert_eq!(ids.len(), 1);
-        assert_eq!(&ids[0].service, &service);
-        assert_eq!(ids[0].status, IdentityStatus::Revoked);
+        assert_eq!(&ids[0].service, &service);
+        assert_eq!(ids[0].status, IdentityStatus::Revoked);
+
+        client.prove_identity(service.clone()).await.unwrap();
+        let ids = client.identity(uid).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(&ids[0].service, &service);
+        assert_eq!(ids[0].status, IdentityStatus::ProofNotFound);
+    }
+
+    #[async_std::test]
+    async fn prove_identity_with_expiry() {
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
+        let github = Service::new("github", "dvc94ch");
+        let gitlab = Service::new("gitlab", "dvc94ch");
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        client.prove_identity(github.clone()).await.unwrap();
+        let options = ProveOptions {
+            expire_in: Some(day * 90),
+        };
+        client
+            .prove_identity_with(gitlab.clone(), options)
+            .await
+            .unwrap();
+
+        assert!(client
+            .expiring_identities(uid, day)
+            .await
+            .unwrap()
+            .is_empty());
+        let ids = client.expiring_identities(uid, day * 91).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0].service, gitlab);
+        assert_eq!(ids[0].status, IdentityStatus::ProofNotFound);
+
+        let options = ProveOptions {
+            expire_in: Some(Duration::from_millis(0)),
+        };
+        client.prove_identity_with(github, options).await.unwrap();
+        let ids = client.expiring_identities(uid, day).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0].status, IdentityStatus::Expired);
+    }
+
+    #[async_std::test]
+    async fn checkpoint() {
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
+        let github = Service::new("github", "dvc94ch");
+        let gitlab = Service::new("gitlab", "dvc94ch");
+
+        client.prove_identity(github.clone()).await.unwrap();
+        client.revoke_identity(github.clone()).await.unwrap();
+        client.prove_identity(github.clone()).await.unwrap();
+        client.prove_identity(gitlab.clone()).await.unwrap();
+        client.revoke_identity(gitlab.clone()).await.unwrap();
+        client.checkpoint().await.unwrap();
+        client.prove_identity(gitlab.clone()).await.unwrap();
+
+        let mut ids = client.identity(uid).await.unwrap();
+        let mut audit = client.audit_identity(uid).await.unwrap();
+        ids.sort_by_key(|id| id.service.to_string());
+        audit.sort_by_key(|id| id.service.to_string());
+        assert_eq!(ids.len(), 2);
+        // the checkpoint drops the claims of the revoked github claim
+        assert_eq!(ids[0].claims.len(), 1);
+        assert_eq!(audit[0].claims.len(), 3);
+        for (id, audit) in ids.iter().zip(audit.iter()) {
+            assert_eq!(id.service, audit.service);
+            assert_eq!(id.status, audit.status);
+        }
+        client.revoke_identity(github).await.unwrap();
+        assert_eq!(client.identity(uid).await.unwrap().len(), 2);
+    }
+
+    #[async_std::test]
+    async fn checkpoint_of_other_uid() {
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Bob).await;
+        let alice = client1
+            .fetch_uid(&AccountKeyring::Alice.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        let bob = client2
+            .fetch_uid(&AccountKeyring::Bob.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        let github = Service::new("github", "dvc94ch");
+        client2.prove_identity(github.clone()).await.unwrap();
+        client1
+            .prove_identity(Service::new("gitlab", "dvc94ch"))
+            .await
+            .unwrap();
+
+        // a checkpoint can't summarize the claims of another uid
+        let cid: Cid = client1
+            .chain_client()
+            .identity(bob, None)
+            .await
+            .unwrap()
+            .unwrap()
+            .into();
+        let claim: Claim = client2.offchain_client().get(&cid).await.unwrap();
+        assert_eq!(client1.offchain_client().insert(claim).await.unwrap(), cid);
+        let claim = create_claim(&client1, ClaimBody::Checkpoint(vec![cid]), None, alice)
+            .await
+            .unwrap();
+        publish_claim(&client1, claim).await.unwrap();
+        assert!(client1.audit_identity(alice).await.is_err());
+        assert!(client1
+            .expiring_identities(alice, Duration::from_secs(0))
+            .await
+            .is_err());
+        // the identity skips the checkpoint
+        let ids = client1.identity(alice).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0].claims.len(), 1);
+    }
 
+    #[async_std::test]
+    async fn verify_identity() {
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
+        let service = Service::new("github", "dvc94ch");
         client.prove_identity(service.clone()).await.unwrap();
+
+        // publish a claim whose signature doesn't match its content
+        let prev = client
+            .chain_client()
+            .identity(uid, None)
+            .await
+            .unwrap()
+            .unwrap();
+        let head: Claim = client
+            .offchain_client()
+            .get(&prev.clone().into())
+            .await
+            .unwrap();
+        let mut forged = head.claim().clone();
+        forged.prev = Some(prev.clone().into());
+        forged.seqno += 1;
+        forged.body = ClaimBody::Revoke(1);
+        let forged = Claim::new(forged, head.signature().to_vec());
+        let root = client.offchain_client().insert(forged).await.unwrap();
+        client
+            .chain_client()
+            .set_identity_and_watch(&client.chain_signer().unwrap(), &Some(prev), &root.into())
+            .await
+            .unwrap();
+        client.prove_identity(service).await.unwrap();
+
+        // the invalid claim is skipped
+        let ids = client.identity(uid).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0].claims.len(), 2);
+        assert_ne!(ids[0].status, IdentityStatus::Revoked);
+        let reports = client.verify_identity(uid).await.unwrap();
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0].status, ClaimStatus::Valid);
+        assert_eq!(
+            reports[1].status,
+            ClaimStatus::Invalid("invalid claim signature".into())
+        );
+        assert_eq!(reports[2].status, ClaimStatus::Valid);
+        assert_eq!(reports[2].key(), Some(account_id.to_ss58check().as_str()));
+    }
+
+    #[async_std::test]
+    async fn attest() {
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
+        let cid1: Cid = "bafyreidogqfzz75tpkmjzjke425xqcrmpcib2p5tg44hnbirumdbpl5adu"
+            .parse()
+            .unwrap();
+        let cid2: Cid = "bafkreicv3gdamutn4d4iwmgdbfyx33xtfqhamhqtdh6r6ihym22juitboq"
+            .parse()
+            .unwrap();
+
+        assert!(client.attest("".into(), cid1.clone()).await.is_err());
+        client.attest("release".into(), cid1.clone()).await.unwrap();
+        client.attest("release".into(), cid2.clone()).await.unwrap();
+        client.attest("audit".into(), cid1.clone()).await.unwrap();
+        assert_eq!(client.attestations(uid, "release").await.unwrap().len(), 2);
+
+        client.revoke_attestation("release", &cid1).await.unwrap();
+        assert!(client.revoke_attestation("release", &cid1).await.is_err());
+        let release = client.attestations(uid, "release").await.unwrap();
+        assert_eq!(release.len(), 1);
+        assert_eq!(
+            release[0].claim().body,
+            ClaimBody::Attestation {
+                topic: "release".into(),
+                data_cid: cid2,
+            }
+        );
+        assert_eq!(client.attestations(uid, "audit").await.unwrap().len(), 1);
+        assert!(client.identity(uid).await.unwrap().is_empty());
+
+        client.checkpoint().await.unwrap();
+        assert_eq!(client.attestations(uid, "release").await.unwrap().len(), 1);
+        assert!(client
+            .verify_identity(uid)
+            .await
+            .unwrap()
+            .iter()
+            .all(|report| report.status == ClaimStatus::Valid));
+    }
+
+    #[async_std::test]
+    async fn sign_as_uid() {
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Eve).await;
+        let alice = AccountKeyring::Alice.to_account_id();
+        let uid = client1.fetch_uid(&alice).await.unwrap().unwrap();
+
+        client1
+            .add_key(client2.signer().unwrap().account_id())
+            .await
+            .unwrap();
+        let envelope = client2.sign_as_uid(b"release v1.0.0").await.unwrap();
+        assert_eq!(envelope.uid, u64::from(uid));
+        let envelope = UidSignature::decode(&envelope.encode().unwrap()).unwrap();
+        client1
+            .verify_uid_signature(&envelope, b"release v1.0.0")
+            .await
+            .unwrap();
+        assert!(client1
+            .verify_uid_signature(&envelope, b"release v1.0.1")
+            .await
+            .is_err());
+
+        // the key was valid when the message was signed
+        client1
+            .remove_key(client2.signer().unwrap().account_id())
+            .await
+            .unwrap();
+        client1
+            .verify_uid_signature(&envelope, b"release v1.0.0")
+            .await
+            .unwrap();
+        let mut other = envelope.clone();
+        other.uid += 1;
+        assert!(client1
+            .verify_uid_signature(&other, b"release v1.0.0")
+            .await
+            .is_err());
+    }
+
+    #[async_std::test]
+    async fn teams() {
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Bob).await;
+        let alice = client1
+            .fetch_uid(&AccountKeyring::Alice.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        let bob = client2
+            .fetch_uid(&AccountKeyring::Bob.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        let password = SecretString::new("password2".to_string());
+        client1.change_password(&password).await.unwrap();
+        client2.change_password(&password).await.unwrap();
+        // The owner needs an encryption key to read the first team key.
+        assert!(client1.create_team().await.is_err());
+        client1.set_encryption_key(&password).await.unwrap();
+        client2.set_encryption_key(&password).await.unwrap();
+
+        let team = client1.create_team().await.unwrap();
+        assert_eq!(
+            client1.members(team).await.unwrap(),
+            vec![(alice, Role::Owner)]
+        );
+        assert!(client2.add_member(team, bob, Role::Reader).await.is_err());
+
+        client1.add_member(team, bob, Role::Admin).await.unwrap();
+        assert!(client2.add_member(team, bob, Role::Owner).await.is_err());
+        client2.add_member(team, bob, Role::Writer).await.unwrap();
+        assert_eq!(
+            client2.members(team).await.unwrap(),
+            vec![(alice, Role::Owner), (bob, Role::Writer)]
+        );
+
+        assert!(client1.remove_member(team, alice).await.is_err());
+        client2.remove_member(team, bob).await.unwrap();
+        assert_eq!(client1.members(team).await.unwrap().len(), 1);
+    }
+
+    #[async_std::test]
+    async fn identity_events() {
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let uid = client
+            .fetch_uid(&AccountKeyring::Alice.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut events = client.subscribe_identity_events(Some(uid)).await.unwrap();
+        let mut all_events = client.subscribe_identity_events(None).await.unwrap();
+
+        let key = AccountKeyring::Eve.to_account_id();
+        client.add_key(&key).await.unwrap();
+        client.remove_key(&key).await.unwrap();
+
+        match all_events.next().await.unwrap().unwrap() {
+            IdentityEvent::KeyAdded(event) => assert_eq!(event.uid, uid),
+            event => panic!("unexpected event {:?}", event),
+        }
+        match events.next().await.unwrap().unwrap() {
+            IdentityEvent::KeyAdded(event) => {
+                assert_eq!(event.uid, uid);
+                assert_eq!(event.key, key);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        match events.next().await.unwrap().unwrap() {
+            IdentityEvent::KeyRemoved(event) => assert_eq!(event.key, key),
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[async_std::test]
+    async fn team_keys() {
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Bob).await;
+        let bob = client2
+            .fetch_uid(&AccountKeyring::Bob.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        let password = SecretString::new("password2".to_string());
+        client1.change_password(&password).await.unwrap();
+        client2.change_password(&password).await.unwrap();
+        client1.set_encryption_key(&password).await.unwrap();
+        client2.set_encryption_key(&password).await.unwrap();
+
+        let team = client1.create_team().await.unwrap();
+        assert_eq!(client1.team_key_gen(team).await.unwrap(), 1);
+        let key = client1.team_key(team, 1, &password).await.unwrap();
+        let data = key.encrypt(b"hello").unwrap();
+        assert_eq!(key.decrypt(&data).unwrap(), b"hello");
+        assert!(client1.team_key(team, 2, &password).await.is_err());
+
+        // Adding a member publishes a key the member can read.
+        client1.add_member(team, bob, Role::Writer).await.unwrap();
+        assert_eq!(client1.team_key_gen(team).await.unwrap(), 2);
+        let gen = client2.rotate_team_key(team).await.unwrap();
+        assert_eq!(gen, 3);
+        client2.team_key(team, gen, &password).await.unwrap();
+
+        // Removing a member rotates the key.
+        client1.remove_member(team, bob).await.unwrap();
+        assert_eq!(client1.team_key_gen(team).await.unwrap(), 4);
+        let cid: Cid = client1
+            .chain_client()
+            .team_keys(team, 4, None)
+            .await
+            .unwrap()
+            .unwrap()
+            .into();
+        let envelope: EncryptedMessage = client1.offchain_client().get(&cid).await.unwrap();
+        let device = AccountKeyring::Bob.to_account_id().to_ss58check();
+        assert!(envelope.recipients.iter().all(|r| r.device != device));
+        let key = client1.team_key(team, 4, &password).await.unwrap();
+        assert!(key.decrypt(&data).is_err());
+
+        // A member leaving revokes the key, the remaining writers rotate it.
+        client1.add_member(team, bob, Role::Reader).await.unwrap();
+        assert_eq!(client1.team_key_gen(team).await.unwrap(), 5);
+        client2.remove_member(team, bob).await.unwrap();
+        assert_eq!(client1.team_key_gen(team).await.unwrap(), 5);
+        assert!(client1
+            .chain_client()
+            .team_key_revoked(team, None)
+            .await
+            .unwrap());
+        client1.rotate_team_keys().await.unwrap();
+        assert_eq!(client1.team_key_gen(team).await.unwrap(), 6);
+        assert!(!client1
+            .chain_client()
+            .team_key_revoked(team, None)
+            .await
+            .unwrap());
+    }
+
+    #[async_std::test]
+    async fn encrypt() {
+        let node = Node::new_mock();
+        let (mut client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Eve).await;
+        let (client3, _tmp) = Client::mock(&node, AccountKeyring::Bob).await;
+        let alice = AccountKeyring::Alice.to_account_id();
+        let uid = client1.fetch_uid(&alice).await.unwrap().unwrap();
+        assert!(client3.encrypt(uid, b"hello").await.is_err());
+
+        client1
+            .add_key(client2.signer().unwrap().account_id())
+            .await
+            .unwrap();
+        let password = SecretString::new("password2".to_string());
+        client2.change_password(&password).await.unwrap();
+        client1.update_password().await.unwrap();
+
+        // Only devices that published an encryption key are recipients.
+        client2.set_encryption_key(&password).await.unwrap();
+        let keys = client3.encryption_keys(uid).await.unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys.iter().filter(|(_, key)| key.is_some()).count(), 1);
+
+        let envelope = client3.encrypt(uid, b"hello").await.unwrap();
+        assert_eq!(envelope.recipients.len(), 1);
+        let envelope = EncryptedMessage::decode(&envelope.encode().unwrap()).unwrap();
+        assert_eq!(
+            client2.decrypt(&password, &envelope).await.unwrap(),
+            b"hello"
+        );
+        assert!(client1.decrypt(&password, &envelope).await.is_err());
+        assert!(client3.decrypt(&password, &envelope).await.is_err());
+
+        client1.set_encryption_key(&password).await.unwrap();
+        let envelope = client3.encrypt(uid, b"hello").await.unwrap();
+        assert_eq!(envelope.recipients.len(), 2);
+        assert_eq!(
+            client1.decrypt(&password, &envelope).await.unwrap(),
+            b"hello"
+        );
+        assert_eq!(
+            client2.decrypt(&password, &envelope).await.unwrap(),
+            b"hello"
+        );
+    }
+
+    #[async_std::test]
+    async fn prove_website() {
+        let _globals = GLOBALS.lock().await;
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
+        let service = Service::new("https", "example.com");
+
+        let proof = client.prove_identity(service.clone()).await.unwrap();
+        let path = "/.well-known/sunshine-identity-proof.md";
+        let base = serve(|_| vec![(path.to_string(), proof)]);
+        register_service(HttpsService::new(&base));
+
         let ids = client.identity(uid).await.unwrap();
         assert_eq!(ids.len(), 1);
         assert_eq!(&ids[0].service, &service);
+        assert_eq!(
+            ids[0].status,
+            IdentityStatus::Active(format!("{}{}", base, path))
+        );
+        assert_eq!(client.resolve(&service).await.unwrap(), uid);
+
+        // the verification result is cached
+        register_service(HttpsService::new("http://127.0.0.1:1"));
+        let ids = client.identity(uid).await.unwrap();
+        assert_eq!(
+            ids[0].status,
+            IdentityStatus::Active(format!("{}{}", base, path))
+        );
+
+        // resolving uses the index when the service is unreachable
+        client.index_identity(uid).await.unwrap();
+        assert_eq!(client.resolve(&service).await.unwrap(), uid);
+
+        set_cache_config(CacheConfig {
+            active_ttl: Duration::from_millis(0),
+            ..Default::default()
+        });
+        // index lookups use expired verification results
+        let resolved = client.resolve(&service).await;
+        let changes = client.reverify_proofs().await;
+        set_cache_config(Default::default());
+        assert_eq!(resolved.unwrap(), uid);
+        let changes = changes.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to, None);
+        assert_eq!(client.proof_status_changes().await.unwrap(), changes);
+        let ids = client.identity(uid).await.unwrap();
+        register_service(HttpsService::default());
         assert_eq!(ids[0].status, IdentityStatus::ProofNotFound);
     }
 
+    #[async_std::test]
+    async fn attach_proof() {
+        let _globals = GLOBALS.lock().await;
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
+        register_service(AttachedService);
+        let service = Service::new("attached", "key");
+
+        let signature = client.prove_identity(service.clone()).await.unwrap();
+        let bad = "key:other".to_string();
+        assert!(client
+            .attach_proof(service.clone(), "key".into(), bad)
+            .await
+            .is_err());
+        let proof = format!("key:{}", signature);
+        client
+            .attach_proof(service.clone(), "key".into(), proof)
+            .await
+            .unwrap();
+
+        let ids = client.identity(uid).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0].claims.len(), 2);
+        assert_eq!(ids[0].status, IdentityStatus::Active("attached:key".into()));
+        assert_eq!(ids[0].key, Some("key".into()));
+        assert_eq!(ids[0].ssh_key(), None);
+
+        client.revoke_identity(service).await.unwrap();
+        let ids = client.identity(uid).await.unwrap();
+        assert_eq!(ids[0].status, IdentityStatus::Revoked);
+    }
+
+    #[async_std::test]
+    async fn cross_chain() {
+        let node1 = Node::new_mock();
+        let node2 = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node1, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node2, AccountKeyring::Bob).await;
+        let alice = AccountKeyring::Alice.to_account_id();
+        let bob = AccountKeyring::Bob.to_account_id();
+        let uid1 = client1.fetch_uid(&alice).await.unwrap().unwrap();
+        let uid2 = client2.fetch_uid(&bob).await.unwrap().unwrap();
+        let genesis1 = client1.chain_client().genesis().as_ref().to_vec();
+
+        let link = client2
+            .sign_cross_chain(genesis1, uid1.into())
+            .await
+            .unwrap();
+        client1.prove_cross_chain(link).await.unwrap();
+        assert_eq!(client1.identity(uid1).await.unwrap().len(), 0);
+
+        let links = client1.cross_chain_links(uid1).await.unwrap();
+        assert_eq!(links.len(), 1);
+        // the link needs to be recorded on both chains
+        assert!(client2.verify_cross_chain(&links[0]).await.is_err());
+
+        let genesis2 = client2.chain_client().genesis().as_ref().to_vec();
+        let link = client1
+            .sign_cross_chain(genesis2, uid2.into())
+            .await
+            .unwrap();
+        client2.prove_cross_chain(link).await.unwrap();
+        let reciprocal = client2.cross_chain_links(uid2).await.unwrap();
+        assert_eq!(reciprocal.len(), 1);
+        client1.verify_cross_chain(&reciprocal[0]).await.unwrap();
+        client2.verify_cross_chain(&links[0]).await.unwrap();
+
+        // the link doesn't hold for another uid
+        let mut unsigned = links[0].claim().clone();
+        unsigned.uid += 1;
+        let forged = Claim::new(unsigned, links[0].signature().to_vec());
+        assert!(client2.verify_cross_chain(&forged).await.is_err());
+    }
+
+    #[async_std::test]
+    async fn track() {
+        let _globals = GLOBALS.lock().await;
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Bob).await;
+        let bob = AccountKeyring::Bob.to_account_id();
+        let uid2 = client2.fetch_uid(&bob).await.unwrap().unwrap();
+        register_service(AttachedService);
+        let service = Service::new("attached", "key");
+
+        let signature = client2.prove_identity(service.clone()).await.unwrap();
+        let proof = format!("key:{}", signature);
+        client2
+            .attach_proof(service.clone(), "key".into(), proof)
+            .await
+            .unwrap();
+        assert!(client1.tracked_changes(uid2).await.unwrap().is_empty());
+
+        client1.track(uid2).await.unwrap();
+        assert!(client1.tracked_changes(uid2).await.unwrap().is_empty());
+        // the tracker's own identities are unaffected
+        let alice = AccountKeyring::Alice.to_account_id();
+        let uid1 = client1.fetch_uid(&alice).await.unwrap().unwrap();
+        assert!(client1.identity(uid1).await.unwrap().is_empty());
+
+        client2.revoke_identity(service.clone()).await.unwrap();
+        assert_eq!(client1.tracked_changes(uid2).await.unwrap(), vec![service]);
+        assert!(client2.tracked_changes(uid2).await.unwrap().is_empty());
+    }
+
     #[async_std::test]
     async fn change_password() {
         let node = Node::new_mock();
@@ -519,11 +2733,138 @@ mod tests {
 
         let event = sub.next().await;
         assert!(event.is_some());
-        client1.update_password().await.unwrap();
+        assert_eq!(
+            client1.update_password().await.unwrap(),
+            PasswordUpdate::Updated
+        );
+        client1.lock().await.unwrap();
+        client1.unlock(&password).await.unwrap();
+    }
+
+    #[async_std::test]
+    async fn reprovision_password() {
+        let node = Node::new_mock();
+        let (mut client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Eve).await;
+
+        let signer2 = client2.signer().unwrap();
+        client1.add_key(signer2.account_id()).await.unwrap();
+
+        let password = SecretString::new("password2".to_string());
+        for _ in 0..17 {
+            client2.change_password(&password).await.unwrap();
+        }
+        assert_eq!(
+            client1.update_password().await.unwrap(),
+            PasswordUpdate::ReprovisionRequired
+        );
+
+        let (pass, gen) = client2.keystore().password().await.unwrap();
+        client1
+            .keystore_mut()
+            .provision_device(&pass, gen)
+            .await
+            .unwrap();
+        assert_eq!(
+            client1.update_password().await.unwrap(),
+            PasswordUpdate::UpToDate
+        );
         client1.lock().await.unwrap();
         client1.unlock(&password).await.unwrap();
     }
 
+    #[async_std::test]
+    async fn close_account() {
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Eve).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let beneficiary = AccountKeyring::Bob.to_account_id();
+        let uid = client1.fetch_uid(&account_id).await.unwrap().unwrap();
+
+        client1
+            .add_key(client2.signer().unwrap().account_id())
+            .await
+            .unwrap();
+        assert!(!client1.close_account(&beneficiary).await.unwrap());
+        assert!(client2.close_account(&beneficiary).await.unwrap());
+        assert!(client1.fetch_keys(uid, None).await.is_err());
+        assert!(client1.add_key(&beneficiary).await.is_err());
+    }
+
+    #[async_std::test]
+    async fn merge_account() {
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Bob).await;
+        let alice = AccountKeyring::Alice.to_account_id();
+        let bob = AccountKeyring::Bob.to_account_id();
+        let uid1 = client1.fetch_uid(&alice).await.unwrap().unwrap();
+        let uid2 = client2.fetch_uid(&bob).await.unwrap().unwrap();
+
+        let service = Service::new("github", "dvc94ch");
+        client2.prove_identity(service.clone()).await.unwrap();
+
+        assert!(!client2.merge_account(uid2, uid1).await.unwrap());
+        assert!(client1.merge_account(uid2, uid1).await.unwrap());
+        assert_eq!(client1.fetch_uid(&bob).await.unwrap(), Some(uid1));
+        assert_eq!(client1.follow_redirect(uid2).await.unwrap(), uid1);
+        assert_eq!(client1.fetch_keys(uid1, None).await.unwrap().len(), 2);
+
+        let ids = client1.identity(uid1).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(&ids[0].service, &service);
+    }
+
+    #[async_std::test]
+    async fn merge_identity_conflict() {
+        let node = Node::new_mock();
+        let (client1, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let (client2, _tmp) = Client::mock(&node, AccountKeyring::Bob).await;
+        let uid1 = client1
+            .fetch_uid(&AccountKeyring::Alice.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        let uid2 = client2
+            .fetch_uid(&AccountKeyring::Bob.to_account_id())
+            .await
+            .unwrap()
+            .unwrap();
+        client1
+            .prove_identity(Service::new("gitlab", "dvc94ch"))
+            .await
+            .unwrap();
+        client2
+            .prove_identity(Service::new("github", "dvc94ch"))
+            .await
+            .unwrap();
+
+        // claim chains can't be combined
+        assert!(client2.merge_account(uid2, uid1).await.is_err());
+        assert_eq!(client1.follow_redirect(uid2).await.unwrap(), uid2);
+    }
+
+    #[async_std::test]
+    async fn set_username() {
+        let node = Node::new_mock();
+        let (client, _tmp) = Client::mock(&node, AccountKeyring::Alice).await;
+        let account_id = AccountKeyring::Alice.to_account_id();
+        let uid = client.fetch_uid(&account_id).await.unwrap().unwrap();
+
+        assert!(client.set_username("a").await.is_err());
+        client.set_username("Alice").await.unwrap();
+        assert_eq!(
+            client.fetch_username(uid).await.unwrap(),
+            Some("alice".to_string())
+        );
+        assert_eq!(client.resolve_username("ALICE").await.unwrap(), uid);
+
+        client.clear_username().await.unwrap();
+        assert_eq!(client.fetch_username(uid).await.unwrap(), None);
+        assert!(client.resolve_username("alice").await.is_err());
+    }
+
     #[async_std::test]
     async fn provision_device() {
         let node = Node::new_mock();
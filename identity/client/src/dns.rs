@@ -0,0 +1,273 @@
+use crate::claim::Claim;
+use crate::error::ProofNotFound;
+use crate::service::{encode_signature, is_domain, ProofParams, ProofService};
+use async_std::task;
+use std::io::{Error, ErrorKind};
+use std::net::{SocketAddr, UdpSocket};
+use std::time::Duration;
+use sunshine_client_utils::{async_trait, Result};
+
+const RECORD_PREFIX: &str = "_sunshine";
+const PROOF_MARKER: &str = "sunshine-identity-proof";
+const TYPE_TXT: u16 = 16;
+const CLASS_IN: u16 = 1;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Proofs published as a TXT record on `_sunshine.<domain>`.
+///
+/// The record has the form `sunshine-identity-proof <uid> <signature>`.
+pub struct DnsService {
+    resolver: SocketAddr,
+}
+
+impl DnsService {
+    /// Creates a dns service that sends queries to `resolver`.
+    pub fn new(resolver: SocketAddr) -> Self {
+        Self { resolver }
+    }
+
+    async fn find_proofs(&self, domain: &str) -> Result<Vec<(String, String)>> {
+        let resolver = self.resolver;
+        let name = format!("{}.{}", RECORD_PREFIX, domain);
+        let records = task::spawn_blocking(move || query_txt(resolver, &name)).await?;
+        Ok(records
+            .iter()
+            .filter_map(|record| {
+                let mut parts = record.split_whitespace();
+                if parts.next() != Some(PROOF_MARKER) {
+                    return None;
+                }
+                let uid = parts.next()?;
+                let signature = parts.next()?;
+                Some((uid.to_string(), signature.to_string()))
+            })
+            .collect())
+    }
+}
+
+impl Default for DnsService {
+    /// Uses the first nameserver in `/etc/resolv.conf`.
+    fn default() -> Self {
+        let resolver = std::fs::read_to_string("/etc/resolv.conf")
+            .ok()
+            .and_then(|conf| {
+                conf.lines()
+                    .filter_map(|line| line.strip_prefix("nameserver"))
+                    .find_map(|addr| addr.trim().parse().ok())
+            })
+            .map(|ip| SocketAddr::new(ip, 53))
+            .unwrap_or_else(|| ([1, 1, 1, 1], 53).into());
+        Self::new(resolver)
+    }
+}
+
+#[async_trait]
+impl ProofService for DnsService {
+    fn name(&self) -> &str {
+        "dns"
+    }
+
+    fn validate(&self, domain: &str) -> bool {
+        is_domain(domain)
+    }
+
+    async fn verify(&self, domain: &str, claim: &Claim) -> Result<String> {
+        let uid = claim.claim().uid.to_string();
+        let signature = encode_signature(claim);
+        Ok(self
+            .find_proofs(domain)
+            .await?
+            .into_iter()
+            .find(|proof| proof == &(uid.clone(), signature.clone()))
+            .map(|_| format!("dns://{}.{}", RECORD_PREFIX, domain))
+            .ok_or(ProofNotFound)?)
+    }
+
+    async fn resolve(&self, domain: &str) -> Result<Vec<String>> {
+        Ok(self
+            .find_proofs(domain)
+            .await?
+            .into_iter()
+            .map(|(uid, _)| uid)
+            .collect())
+    }
+
+    fn proof(&self, params: &ProofParams<'_>) -> String {
+        format!("{} {} {}\n", PROOF_MARKER, params.uid, params.signature)
+    }
+
+    fn cli_instructions(&self, domain: &str) -> String {
+        format!(
+            "Please add the following TXT record to {}.{}\n",
+            RECORD_PREFIX, domain
+        )
+    }
+}
+
+fn invalid_data(msg: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Encodes a recursive query for the TXT records of `name`.
+fn encode_query(id: u16, name: &str) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(name.len() + 18);
+    buf.extend_from_slice(&id.to_be_bytes());
+    // recursion desired
+    buf.extend_from_slice(&0x0100u16.to_be_bytes());
+    // one question, no answer, authority or additional records
+    buf.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.') {
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+    buf.extend_from_slice(&TYPE_TXT.to_be_bytes());
+    buf.extend_from_slice(&CLASS_IN.to_be_bytes());
+    buf
+}
+
+/// Returns the offset after the name starting at `pos`.
+fn skip_name(buf: &[u8], mut pos: usize) -> std::io::Result<usize> {
+    loop {
+        let len = *buf.get(pos).ok_or_else(|| invalid_data("truncated name"))?;
+        if len & 0xc0 == 0xc0 {
+            return Ok(pos + 2);
+        }
+        if len == 0 {
+            return Ok(pos + 1);
+        }
+        pos += 1 + len as usize;
+    }
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> std::io::Result<u16> {
+    match buf.get(pos..pos + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err(invalid_data("truncated message")),
+    }
+}
+
+/// Decodes the TXT records of a response to the query with `id`.
+fn decode_response(id: u16, buf: &[u8]) -> std::io::Result<Vec<String>> {
+    if read_u16(buf, 0)? != id {
+        return Err(invalid_data("unexpected message id"));
+    }
+    let flags = read_u16(buf, 2)?;
+    match flags & 0x000f {
+        0 => {}
+        // NXDOMAIN
+        3 => return Ok(Vec::new()),
+        _ => return Err(Error::new(ErrorKind::Other, "dns query failed")),
+    }
+    let questions = read_u16(buf, 4)?;
+    let answers = read_u16(buf, 6)?;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(buf, pos)? + 4;
+    }
+    let mut records = Vec::with_capacity(answers as usize);
+    for _ in 0..answers {
+        pos = skip_name(buf, pos)?;
+        let ty = read_u16(buf, pos)?;
+        let len = read_u16(buf, pos + 8)? as usize;
+        pos += 10;
+        let rdata = buf
+            .get(pos..pos + len)
+            .ok_or_else(|| invalid_data("truncated record"))?;
+        pos += len;
+        if ty != TYPE_TXT {
+            continue;
+        }
+        // a TXT record is a sequence of length prefixed strings
+        let mut record = Vec::with_capacity(len);
+        let mut i = 0;
+        while i < rdata.len() {
+            let slen = rdata[i] as usize;
+            let s = rdata
+                .get(i + 1..i + 1 + slen)
+                .ok_or_else(|| invalid_data("truncated record"))?;
+            record.extend_from_slice(s);
+            i += 1 + slen;
+        }
+        records.push(String::from_utf8_lossy(&record).into_owned());
+    }
+    Ok(records)
+}
+
+/// Queries `resolver` for the TXT records of `name`.
+fn query_txt(resolver: SocketAddr, name: &str) -> std::io::Result<Vec<String>> {
+    let local: SocketAddr = if resolver.is_ipv4() {
+        ([0, 0, 0, 0], 0).into()
+    } else {
+        ([0u16; 8], 0).into()
+    };
+    let socket = UdpSocket::bind(local)?;
+    socket.set_read_timeout(Some(TIMEOUT))?;
+    socket.connect(resolver)?;
+    let id = std::process::id() as u16 ^ name.len() as u16;
+    socket.send(&encode_query(id, name))?;
+    let mut buf = [0; 4096];
+    let len = socket.recv(&mut buf)?;
+    decode_response(id, &buf[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::tests::claim;
+    use crate::service::{register_service, Service};
+
+    /// Answers TXT queries for `name` with `records` on a local port.
+    fn stub(name: &'static str, records: Vec<String>) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf) {
+                let query = &buf[..len];
+                let end = skip_name(query, 12).unwrap() + 4;
+                let mut res = query[..end].to_vec();
+                let found = encode_query(0, name)[12..] == query[12..end];
+                // response, recursion desired and available, NXDOMAIN if not found
+                res[2..4].copy_from_slice(if found { &[0x81, 0x80] } else { &[0x81, 0x83] });
+                let answers = if found { records.len() as u16 } else { 0 };
+                res[6..8].copy_from_slice(&answers.to_be_bytes());
+                for record in records.iter().take(answers as usize) {
+                    res.extend_from_slice(&[0xc0, 12]);
+                    res.extend_from_slice(&TYPE_TXT.to_be_bytes());
+                    res.extend_from_slice(&CLASS_IN.to_be_bytes());
+                    res.extend_from_slice(&300u32.to_be_bytes());
+                    res.extend_from_slice(&(record.len() as u16 + 1).to_be_bytes());
+                    res.push(record.len() as u8);
+                    res.extend_from_slice(record.as_bytes());
+                }
+                socket.send_to(&res, peer).unwrap();
+            }
+        });
+        addr
+    }
+
+    #[async_std::test]
+    async fn test_verify_txt_record() {
+        let other = claim(&Service::new("dns", "example.com"), b"other");
+        let claim = claim(&Service::new("dns", "example.com"), b"signature");
+        let proof = Service::new("dns", "example.com").proof(&claim).unwrap();
+        let resolver = stub(
+            "_sunshine.example.com",
+            vec!["v=spf1 -all".into(), proof.trim().into()],
+        );
+
+        let service = DnsService::new(resolver);
+        let url = service.verify("example.com", &claim).await.unwrap();
+        assert_eq!(url, "dns://_sunshine.example.com");
+        assert!(service.verify("example.com", &other).await.is_err());
+        assert!(service.verify("example.org", &claim).await.is_err());
+        let uids = service.resolve("example.com").await.unwrap();
+        assert_eq!(uids, vec!["42".to_string()]);
+
+        register_service(DnsService::new(resolver));
+        let service: Service = "example.com@dns".parse().unwrap();
+        service.verify(&claim).await.unwrap();
+        assert!(service.verify(&other).await.is_err());
+    }
+}
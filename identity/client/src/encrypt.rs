@@ -0,0 +1,220 @@
+use crate::error::{DecryptionFailed, EncryptionFailed, NotRecipient};
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use libipld::cbor::DagCborCodec;
+use libipld::codec::Codec as _;
+use libipld::multibase::{decode, encode, Base};
+use libipld::DagCbor;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use substrate_subxt::sp_core::hashing::blake2_256;
+use sunshine_client_utils::Result;
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, StaticSecret};
+
+/// Domain separator of the key that wraps the message key.
+const WRAP_KEY_PREFIX: &[u8] = b"sunshine-identity-encryption";
+
+/// Domain separator of the X25519 key derived from a device key.
+const ENCRYPTION_KEY_PREFIX: &[u8] = b"sunshine-identity-x25519";
+
+/// Domain separator of the associated data of the ciphertext.
+const ASSOCIATED_DATA_PREFIX: &[u8] = b"sunshine-identity-envelope";
+
+/// The message key encrypted to a single device.
+#[derive(Clone, Debug, Eq, PartialEq, DagCbor)]
+pub struct Recipient {
+    /// The device the message key is encrypted to.
+    pub device: String,
+    /// The ephemeral public key of the key exchange.
+    pub ephemeral: Vec<u8>,
+    /// The encrypted message key.
+    pub key: Vec<u8>,
+}
+
+/// A message encrypted to the devices of a uid.
+#[derive(Clone, Debug, Eq, PartialEq, DagCbor)]
+pub struct EncryptedMessage {
+    /// The user the message is encrypted to.
+    pub uid: u64,
+    pub recipients: Vec<Recipient>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl EncryptedMessage {
+    /// Encodes the envelope as multibase.
+    pub fn encode(&self) -> Result<String> {
+        Ok(encode(Base::Base64, DagCborCodec.encode(self)?))
+    }
+
+    /// Decodes an envelope encoded with `encode`.
+    pub fn decode(s: &str) -> Result<Self> {
+        let (_, bytes) = decode(s.trim())?;
+        Ok(DagCborCodec.decode(&bytes)?)
+    }
+}
+
+/// Derives the key that wraps the message key from the shared secret of a
+/// key exchange.
+fn wrap_key(shared: &[u8], ephemeral: &[u8], public: &[u8]) -> ChaCha20Poly1305 {
+    let mut bytes = WRAP_KEY_PREFIX.to_vec();
+    bytes.extend_from_slice(shared);
+    bytes.extend_from_slice(ephemeral);
+    bytes.extend_from_slice(public);
+    ChaCha20Poly1305::new(GenericArray::from_slice(&blake2_256(&bytes)))
+}
+
+/// Returns the associated data of the ciphertext, so that the uid and the
+/// recipients of an envelope can't be changed without failing to decrypt.
+fn associated_data(uid: u64, recipients: &[Recipient]) -> [u8; 32] {
+    let mut bytes = ASSOCIATED_DATA_PREFIX.to_vec();
+    bytes.extend_from_slice(&uid.to_le_bytes());
+    for recipient in recipients {
+        for field in &[
+            recipient.device.as_bytes(),
+            &recipient.ephemeral[..],
+            &recipient.key[..],
+        ] {
+            bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+    }
+    blake2_256(&bytes)
+}
+
+/// Derives the X25519 secret of a device from its raw sr25519 secret key.
+fn encryption_secret(secret: &[u8]) -> StaticSecret {
+    let mut bytes = ENCRYPTION_KEY_PREFIX.to_vec();
+    bytes.extend_from_slice(secret);
+    StaticSecret::from(blake2_256(&bytes))
+}
+
+/// Returns the X25519 encryption key a device publishes.
+pub(crate) fn encryption_key(secret: &[u8]) -> [u8; 32] {
+    *X25519PublicKey::from(&encryption_secret(secret)).as_bytes()
+}
+
+/// A device a message is encrypted to.
+pub(crate) struct Device {
+    pub ss58: String,
+    /// The X25519 key published by the device.
+    pub encryption_key: [u8; 32],
+}
+
+/// Encrypts a message to a list of devices.
+///
+/// The message key is encrypted to the published X25519 key of a device
+/// with an ephemeral Diffie-Hellman key exchange. The sr25519 signing key
+/// is never used for the key exchange. Every wrap key is used only once, so
+/// the nonce of the wrapped key is zero. The uid and the recipients are
+/// authenticated as associated data of the ciphertext.
+pub(crate) fn seal(uid: u64, devices: &[Device], msg: &[u8]) -> Result<EncryptedMessage> {
+    let mut key = [0; 32];
+    OsRng.fill_bytes(&mut key);
+    let mut recipients = Vec::with_capacity(devices.len());
+    for device in devices {
+        let secret = EphemeralSecret::new(OsRng);
+        let ephemeral = X25519PublicKey::from(&secret);
+        let shared = secret.diffie_hellman(&X25519PublicKey::from(device.encryption_key));
+        let wrapped = wrap_key(
+            shared.as_bytes(),
+            ephemeral.as_bytes(),
+            &device.encryption_key,
+        )
+        .encrypt(GenericArray::from_slice(&[0; 12]), &key[..])
+        .map_err(|_| EncryptionFailed)?;
+        recipients.push(Recipient {
+            device: device.ss58.clone(),
+            ephemeral: ephemeral.as_bytes().to_vec(),
+            key: wrapped,
+        });
+    }
+    let mut nonce = [0; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let aad = associated_data(uid, &recipients);
+    let ciphertext = ChaCha20Poly1305::new(GenericArray::from_slice(&key))
+        .encrypt(GenericArray::from_slice(&nonce), Payload { msg, aad: &aad })
+        .map_err(|_| EncryptionFailed)?;
+    Ok(EncryptedMessage {
+        uid,
+        recipients,
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+/// Decrypts a message with the raw sr25519 secret key of a device.
+pub(crate) fn open(envelope: &EncryptedMessage, device: &str, secret: &[u8]) -> Result<Vec<u8>> {
+    let recipient = envelope
+        .recipients
+        .iter()
+        .find(|recipient| recipient.device == device)
+        .ok_or(NotRecipient)?;
+    if recipient.ephemeral.len() != 32 || envelope.nonce.len() != 12 {
+        return Err(DecryptionFailed.into());
+    }
+    let mut ephemeral = [0; 32];
+    ephemeral.copy_from_slice(&recipient.ephemeral);
+    let secret = encryption_secret(secret);
+    let public = X25519PublicKey::from(&secret);
+    let shared = secret.diffie_hellman(&X25519PublicKey::from(ephemeral));
+    let key = wrap_key(shared.as_bytes(), &ephemeral, public.as_bytes())
+        .decrypt(GenericArray::from_slice(&[0; 12]), &recipient.key[..])
+        .map_err(|_| DecryptionFailed)?;
+    if key.len() != 32 {
+        return Err(DecryptionFailed.into());
+    }
+    let aad = associated_data(envelope.uid, &envelope.recipients);
+    let msg = ChaCha20Poly1305::new(GenericArray::from_slice(&key))
+        .decrypt(
+            GenericArray::from_slice(&envelope.nonce),
+            Payload {
+                msg: &envelope.ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| DecryptionFailed)?;
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use substrate_subxt::sp_core::crypto::Pair as _;
+    use substrate_subxt::sp_core::sr25519::Pair;
+
+    #[test]
+    fn test_seal_open() {
+        let alice = Pair::from_string("//Alice", None).unwrap();
+        let bob = Pair::from_string("//Bob", None).unwrap();
+        let eve = Pair::from_string("//Eve", None).unwrap();
+        let devices = [
+            Device {
+                ss58: "alice".into(),
+                encryption_key: encryption_key(&alice.to_raw_vec()),
+            },
+            Device {
+                ss58: "bob".into(),
+                encryption_key: encryption_key(&bob.to_raw_vec()),
+            },
+        ];
+        let envelope = seal(42, &devices, b"hello").unwrap();
+        let mut envelope = EncryptedMessage::decode(&envelope.encode().unwrap()).unwrap();
+        assert_eq!(envelope.recipients.len(), 2);
+        assert_eq!(
+            open(&envelope, "alice", &alice.to_raw_vec()).unwrap(),
+            b"hello"
+        );
+        assert_eq!(open(&envelope, "bob", &bob.to_raw_vec()).unwrap(), b"hello");
+        assert!(open(&envelope, "eve", &eve.to_raw_vec()).is_err());
+        assert!(open(&envelope, "bob", &eve.to_raw_vec()).is_err());
+
+        // The uid and the recipients are authenticated.
+        envelope.uid = 43;
+        assert!(open(&envelope, "alice", &alice.to_raw_vec()).is_err());
+        envelope.uid = 42;
+        envelope.recipients.pop();
+        assert!(open(&envelope, "alice", &alice.to_raw_vec()).is_err());
+    }
+}
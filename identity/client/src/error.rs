@@ -23,3 +23,47 @@ pub struct NoBlockHash;
 #[derive(Debug, Error)]
 #[error("runtime invalid")]
 pub struct RuntimeInvalid;
+
+#[derive(Debug, Error)]
+#[error("invalid username")]
+pub struct InvalidUsername;
+
+#[derive(Debug, Error)]
+#[error("attestation not found")]
+pub struct AttestationNotFound;
+
+#[derive(Debug, Error)]
+#[error("invalid signature {0}")]
+pub struct InvalidSignature(pub &'static str);
+
+#[derive(Debug, Error)]
+#[error("invalid key")]
+pub struct InvalidKey;
+
+#[derive(Debug, Error)]
+#[error("failed to encrypt message")]
+pub struct EncryptionFailed;
+
+#[derive(Debug, Error)]
+#[error("failed to decrypt message")]
+pub struct DecryptionFailed;
+
+#[derive(Debug, Error)]
+#[error("device is not a recipient of the message")]
+pub struct NotRecipient;
+
+#[derive(Debug, Error)]
+#[error("no device published an encryption key")]
+pub struct NoEncryptionKey;
+
+#[derive(Debug, Error)]
+#[error("team key not found")]
+pub struct NoTeamKey;
+
+#[derive(Debug, Error)]
+#[error("team key generation overflow")]
+pub struct TeamKeyGenOverflow;
+
+#[derive(Debug, Error)]
+#[error("both accounts have an identity, only an account without claims can be merged")]
+pub struct IdentityConflict;
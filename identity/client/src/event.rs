@@ -0,0 +1,76 @@
+use crate::subxt::*;
+use codec::Decode;
+use substrate_subxt::{Event, EventSubscription, RawEvent};
+use sunshine_client_utils::Result;
+
+/// An identity event decoded from the chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IdentityEvent<T: Identity> {
+    AccountCreated(AccountCreatedEvent<T>),
+    KeyAdded(KeyAddedEvent<T>),
+    KeyRemoved(KeyRemovedEvent<T>),
+    IdentityChanged(IdentityChangedEvent<T>),
+    PasswordChanged(PasswordChangedEvent<T>),
+}
+
+impl<T: Identity> IdentityEvent<T> {
+    /// Returns the uid the event belongs to.
+    pub fn uid(&self) -> T::Uid {
+        match self {
+            Self::AccountCreated(event) => event.uid,
+            Self::KeyAdded(event) => event.uid,
+            Self::KeyRemoved(event) => event.uid,
+            Self::IdentityChanged(event) => event.uid,
+            Self::PasswordChanged(event) => event.uid,
+        }
+    }
+
+    /// Decodes a raw event. Returns `None` if it isn't an identity event.
+    fn decode(raw: &RawEvent) -> Option<Result<Self>> {
+        decode_event(raw, Self::AccountCreated)
+            .or_else(|| decode_event(raw, Self::KeyAdded))
+            .or_else(|| decode_event(raw, Self::KeyRemoved))
+            .or_else(|| decode_event(raw, Self::IdentityChanged))
+            .or_else(|| decode_event(raw, Self::PasswordChanged))
+    }
+}
+
+fn decode_event<T: Identity, E: Event<T>>(
+    raw: &RawEvent,
+    f: impl FnOnce(E) -> IdentityEvent<T>,
+) -> Option<Result<IdentityEvent<T>>> {
+    if raw.module != E::MODULE || raw.variant != E::EVENT {
+        return None;
+    }
+    Some(E::decode(&mut &raw.data[..]).map(f).map_err(Into::into))
+}
+
+/// Subscription to the identity events, optionally of a single uid.
+pub struct IdentityEventSubscription<T: Identity> {
+    subscription: EventSubscription<T>,
+    uid: Option<T::Uid>,
+}
+
+impl<T: Identity> IdentityEventSubscription<T> {
+    pub(crate) fn new(subscription: EventSubscription<T>, uid: Option<T::Uid>) -> Self {
+        Self { subscription, uid }
+    }
+
+    /// Waits for the next event that matches the filter.
+    pub async fn next(&mut self) -> Option<Result<IdentityEvent<T>>> {
+        while let Some(raw) = self.subscription.next().await {
+            let raw = match raw {
+                Ok(raw) => raw,
+                Err(err) => return Some(Err(err.into())),
+            };
+            match IdentityEvent::decode(&raw) {
+                Some(Ok(event)) if self.uid.map_or(true, |uid| uid == event.uid()) => {
+                    return Some(Ok(event));
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                _ => {}
+            }
+        }
+        None
+    }
+}
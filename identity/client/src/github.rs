@@ -1,8 +1,10 @@
+use crate::claim::Claim;
 use crate::error::ProofNotFound;
+use crate::service::{fetch, parse_proof, proof_markdown, ProofParams, ProofService};
 use async_std::task;
 use serde::Deserialize;
 use std::collections::BTreeMap;
-use sunshine_client_utils::Result;
+use sunshine_client_utils::{async_trait, Result};
 
 #[derive(Deserialize)]
 struct Gist {
@@ -22,78 +24,108 @@ struct Proof {
 
 const GIST_NAME: &str = "sunshine-identity-proof.md";
 
-async fn find_proofs(user: &str) -> Result<Vec<Proof>> {
-    let uri = format!("https://api.github.com/users/{}/gists", user);
-    let gists: Vec<Gist> =
-        task::spawn_blocking(move || ureq::get(&uri).call().into_json_deserialize()).await?;
-    let mut proofs = Vec::with_capacity(gists.len());
-    let urls = gists.into_iter().filter_map(|mut g| {
-        g.files
-            .remove(GIST_NAME)
-            .map(|file| (g.html_url, file.raw_url))
-    });
-    for (html_url, raw_url) in urls {
-        let res = task::spawn_blocking(move || ureq::get(&raw_url).call()).await;
-        let content = res.into_string()?;
-        proofs.push(Proof { html_url, content });
-    }
-    Ok(proofs)
+/// Proofs published as a github gist.
+pub struct GithubService {
+    api: String,
 }
 
-pub async fn verify(user: &str, signature: &str) -> Result<String> {
-    Ok(find_proofs(user)
-        .await?
-        .into_iter()
-        .filter_map(|proof| {
-            if let Some(signature2) = proof.content.lines().nth(17) {
-                if signature == signature2 {
-                    return Some(proof.html_url);
-                }
-            }
-            None
-        })
-        .next()
-        .ok_or(ProofNotFound)?)
+impl GithubService {
+    /// Creates a github service using a different api endpoint.
+    pub fn new(api: &str) -> Self {
+        Self {
+            api: api.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn find_proofs(&self, user: &str) -> Result<Vec<Proof>> {
+        let uri = format!("{}/users/{}/gists", self.api, user);
+        let gists: Vec<Gist> =
+            task::spawn_blocking(move || ureq::get(&uri).call().into_json_deserialize()).await?;
+        let mut proofs = Vec::with_capacity(gists.len());
+        let urls = gists.into_iter().filter_map(|mut g| {
+            g.files
+                .remove(GIST_NAME)
+                .map(|file| (g.html_url, file.raw_url))
+        });
+        for (html_url, raw_url) in urls {
+            let content = fetch(raw_url).await?;
+            proofs.push(Proof { html_url, content });
+        }
+        Ok(proofs)
+    }
 }
 
-pub async fn resolve(user: &str) -> Result<Vec<String>> {
-    Ok(find_proofs(user)
-        .await?
-        .into_iter()
-        .filter_map(|proof| {
-            if let Some(line) = proof.content.lines().nth(5) {
-                line.split_whitespace().nth(3).map(|s| s.to_string())
-            } else {
-                None
-            }
-        })
-        .collect())
+impl Default for GithubService {
+    fn default() -> Self {
+        Self::new("https://api.github.com")
+    }
 }
 
-pub fn proof(
-    genesis: &str,
-    block: &str,
-    uid: &str,
-    username: &str,
-    public: &str,
-    object: &str,
-    signature: &str,
-) -> String {
-    format!(
-        include_str!("../github-proof-template.md"),
-        genesis = genesis,
-        block = block,
-        uid = uid,
-        username = username,
-        public = public,
-        object = object,
-        signature = signature,
-    )
+#[async_trait]
+impl ProofService for GithubService {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    async fn verify(&self, user: &str, claim: &Claim) -> Result<String> {
+        Ok(self
+            .find_proofs(user)
+            .await?
+            .into_iter()
+            .find(|proof| parse_proof(&proof.content).as_ref() == Some(claim))
+            .map(|proof| proof.html_url)
+            .ok_or(ProofNotFound)?)
+    }
+
+    async fn resolve(&self, user: &str) -> Result<Vec<String>> {
+        Ok(self
+            .find_proofs(user)
+            .await?
+            .into_iter()
+            .filter_map(|proof| parse_proof(&proof.content))
+            .map(|claim| claim.claim().uid.to_string())
+            .collect())
+    }
+
+    fn proof(&self, params: &ProofParams<'_>) -> String {
+        proof_markdown(
+            params,
+            &format!("I am {} on github.", params.username),
+            "I am proving ownership of the github account by posting this as a gist",
+        )
+    }
+
+    fn cli_instructions(&self, _username: &str) -> String {
+        format!(
+            include_str!("../github-proof-instructions.md"),
+            gist_name = GIST_NAME,
+        )
+    }
 }
 
-pub fn cli_instructions() -> String {
-    format!(
-        include_str!("../github-proof-instructions.md"),
-        gist_name = GIST_NAME,
-    )
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::tests::test_proof_service;
+    use crate::service::Service;
+
+    #[async_std::test]
+    async fn test_verify_gist() {
+        test_proof_service(
+            &Service::new("github", "dvc94ch"),
+            |base, proof| {
+                let gists = format!(
+                    r#"[{{"html_url":"{0}/gist","files":{{"{1}":{{"raw_url":"{0}/raw"}}}}}}]"#,
+                    base, GIST_NAME,
+                );
+                vec![
+                    ("/users/dvc94ch/gists".into(), gists),
+                    ("/raw".into(), proof),
+                ]
+            },
+            GithubService::new,
+            |base| format!("{}/gist", base),
+        )
+        .await;
+    }
 }
@@ -0,0 +1,140 @@
+use crate::claim::Claim;
+use crate::error::ProofNotFound;
+use crate::service::{fetch, parse_proof, proof_markdown, ProofParams, ProofService};
+use async_std::task;
+use serde::Deserialize;
+use sunshine_client_utils::{async_trait, Result};
+
+#[derive(Deserialize)]
+struct Snippet {
+    web_url: String,
+    raw_url: String,
+    file_name: String,
+}
+
+struct Proof {
+    web_url: String,
+    content: String,
+}
+
+const PROJECT_NAME: &str = "sunshine-identity-proof";
+const SNIPPET_NAME: &str = "sunshine-identity-proof.md";
+
+/// Proofs published as a snippet of the `sunshine-identity-proof` project
+/// on gitlab.
+pub struct GitlabService {
+    base: String,
+}
+
+impl GitlabService {
+    /// Creates a gitlab service for a self hosted gitlab instance.
+    pub fn new(base: &str) -> Self {
+        Self {
+            base: base.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn find_proofs(&self, user: &str) -> Result<Vec<Proof>> {
+        let uri = format!(
+            "{}/api/v4/projects/{}%2F{}/snippets",
+            self.base, user, PROJECT_NAME
+        );
+        let snippets: Vec<Snippet> =
+            task::spawn_blocking(move || ureq::get(&uri).call().into_json_deserialize()).await?;
+        let mut proofs = Vec::with_capacity(snippets.len());
+        let urls = snippets
+            .into_iter()
+            .filter(|s| s.file_name == SNIPPET_NAME)
+            .map(|s| (s.web_url, s.raw_url));
+        for (web_url, raw_url) in urls {
+            let content = fetch(raw_url).await?;
+            proofs.push(Proof { web_url, content });
+        }
+        Ok(proofs)
+    }
+}
+
+impl Default for GitlabService {
+    fn default() -> Self {
+        Self::new("https://gitlab.com")
+    }
+}
+
+#[async_trait]
+impl ProofService for GitlabService {
+    fn name(&self) -> &str {
+        "gitlab"
+    }
+
+    fn validate(&self, username: &str) -> bool {
+        !username.is_empty() && !username.contains('/')
+    }
+
+    async fn verify(&self, user: &str, claim: &Claim) -> Result<String> {
+        Ok(self
+            .find_proofs(user)
+            .await?
+            .into_iter()
+            .find(|proof| parse_proof(&proof.content).as_ref() == Some(claim))
+            .map(|proof| proof.web_url)
+            .ok_or(ProofNotFound)?)
+    }
+
+    async fn resolve(&self, user: &str) -> Result<Vec<String>> {
+        Ok(self
+            .find_proofs(user)
+            .await?
+            .into_iter()
+            .filter_map(|proof| parse_proof(&proof.content))
+            .map(|claim| claim.claim().uid.to_string())
+            .collect())
+    }
+
+    fn proof(&self, params: &ProofParams<'_>) -> String {
+        proof_markdown(
+            params,
+            &format!("I am {} on gitlab.", params.username),
+            "I am proving ownership of the gitlab account by posting this as a snippet",
+        )
+    }
+
+    fn cli_instructions(&self, username: &str) -> String {
+        format!(
+            include_str!("../gitlab-proof-instructions.md"),
+            base = self.base,
+            username = username,
+            project_name = PROJECT_NAME,
+            snippet_name = SNIPPET_NAME,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::tests::test_proof_service;
+    use crate::service::Service;
+
+    #[async_std::test]
+    async fn test_verify_snippet() {
+        test_proof_service(
+            &Service::new("gitlab", "dvc94ch"),
+            |base, proof| {
+                let snippets = format!(
+                    r#"[{{"web_url":"{0}/snippet","raw_url":"{0}/raw","file_name":"{1}"}}]"#,
+                    base, SNIPPET_NAME,
+                );
+                vec![
+                    (
+                        "/api/v4/projects/dvc94ch%2Fsunshine-identity-proof/snippets".into(),
+                        snippets,
+                    ),
+                    ("/raw".into(), proof),
+                ]
+            },
+            GitlabService::new,
+            |base| format!("{}/snippet", base),
+        )
+        .await;
+    }
+}
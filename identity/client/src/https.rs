@@ -0,0 +1,111 @@
+use crate::claim::Claim;
+use crate::error::ProofNotFound;
+use crate::service::{fetch, is_domain, parse_proof, proof_markdown, ProofParams, ProofService};
+use sunshine_client_utils::{async_trait, Result};
+
+const PROOF_PATH: &str = ".well-known/sunshine-identity-proof.md";
+
+/// Proofs published on a domain under
+/// `/.well-known/sunshine-identity-proof.md`.
+#[derive(Default)]
+pub struct HttpsService {
+    base_url: Option<String>,
+}
+
+impl HttpsService {
+    /// Creates a https service that fetches proofs from `base_url` instead
+    /// of `https://<domain>`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: Some(base_url.trim_end_matches('/').to_string()),
+        }
+    }
+
+    fn url(&self, domain: &str, path: &str) -> String {
+        match &self.base_url {
+            Some(base_url) => format!("{}/{}", base_url, path),
+            None => format!("https://{}/{}", domain, path),
+        }
+    }
+
+    /// Returns the proof found on the domain and its url.
+    async fn find_proof(&self, domain: &str) -> Option<(String, Claim)> {
+        let url = self.url(domain, PROOF_PATH);
+        let content = fetch(url.clone()).await.ok()?;
+        let claim = parse_proof(&content)?;
+        Some((url, claim))
+    }
+}
+
+#[async_trait]
+impl ProofService for HttpsService {
+    fn name(&self) -> &str {
+        "https"
+    }
+
+    fn validate(&self, domain: &str) -> bool {
+        is_domain(domain)
+    }
+
+    async fn verify(&self, domain: &str, claim: &Claim) -> Result<String> {
+        Ok(self
+            .find_proof(domain)
+            .await
+            .filter(|(_, proof)| proof == claim)
+            .map(|(url, _)| url)
+            .ok_or(ProofNotFound)?)
+    }
+
+    async fn resolve(&self, domain: &str) -> Result<Vec<String>> {
+        Ok(self
+            .find_proof(domain)
+            .await
+            .map(|(_, claim)| claim.claim().uid.to_string())
+            .into_iter()
+            .collect())
+    }
+
+    fn proof(&self, params: &ProofParams<'_>) -> String {
+        proof_markdown(
+            params,
+            &format!("I control the domain {}.", params.username),
+            "I am proving control of the domain by publishing this file on it",
+        )
+    }
+
+    fn cli_instructions(&self, domain: &str) -> String {
+        format!(
+            include_str!("../https-proof-instructions.md"),
+            url = self.url(domain, PROOF_PATH),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::tests::test_proof_service;
+    use crate::service::Service;
+
+    #[test]
+    fn test_validate_domain() {
+        let service = HttpsService::default();
+        assert!(service.validate("example.com"));
+        assert!(service.validate("sub.example-1.com"));
+        assert!(!service.validate("localhost"));
+        assert!(!service.validate("example..com"));
+        assert!(!service.validate("example.com/path"));
+        assert!(!service.validate("-example.com"));
+    }
+
+    #[async_std::test]
+    async fn test_verify_well_known() {
+        test_proof_service(
+            &Service::new("https", "example.com"),
+            |_, proof| vec![(format!("/{}", PROOF_PATH), proof)],
+            HttpsService::new,
+            |base| format!("{}/{}", base, PROOF_PATH),
+        )
+        .await;
+    }
+}
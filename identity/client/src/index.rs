@@ -0,0 +1,98 @@
+use crate::service::Service;
+use async_std::sync::Mutex;
+use libipld::DagCbor;
+use once_cell::sync::Lazy;
+
+/// Serializes updates of the service index.
+pub(crate) static SERVICE_INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// The uids with an active ownership claim of a service.
+#[derive(Clone, Debug, Eq, PartialEq, DagCbor)]
+pub struct IndexEntry {
+    pub service: Service,
+    pub uids: Vec<u64>,
+}
+
+/// Maps services to the uids that claim them.
+///
+/// The index is built from the claims published on chain, so it can be
+/// consulted without asking the service. A claim in the index still needs
+/// its proof verified before it is trusted.
+#[derive(Clone, Debug, Default, Eq, PartialEq, DagCbor)]
+pub struct ServiceIndex {
+    pub entries: Vec<IndexEntry>,
+}
+
+impl ServiceIndex {
+    /// Returns the uids that claim a service.
+    pub fn uids(&self, service: &Service) -> &[u64] {
+        self.entries
+            .iter()
+            .find(|entry| &entry.service == service)
+            .map(|entry| &entry.uids[..])
+            .unwrap_or_default()
+    }
+
+    /// Adds a uid to the claimants of a service. Returns `true` if the
+    /// index changed.
+    pub fn insert(&mut self, service: &Service, uid: u64) -> bool {
+        match self.entries.iter_mut().find(|e| &e.service == service) {
+            Some(entry) if entry.uids.contains(&uid) => false,
+            Some(entry) => {
+                entry.uids.push(uid);
+                true
+            }
+            None => {
+                self.entries.push(IndexEntry {
+                    service: service.clone(),
+                    uids: vec![uid],
+                });
+                true
+            }
+        }
+    }
+
+    /// Replaces the services claimed by a uid. Returns `true` if the index
+    /// changed.
+    pub fn set_claims(&mut self, uid: u64, services: &[Service]) -> bool {
+        let mut changed = false;
+        for entry in &mut self.entries {
+            if !services.contains(&entry.service) && entry.uids.contains(&uid) {
+                entry.uids.retain(|u| *u != uid);
+                changed = true;
+            }
+        }
+        self.entries.retain(|entry| !entry.uids.is_empty());
+        for service in services {
+            changed |= self.insert(service, uid);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_index() {
+        let github = Service::new("github", "dvc94ch");
+        let dns = Service::new("dns", "example.com");
+        let mut index = ServiceIndex::default();
+
+        assert!(index.uids(&github).is_empty());
+        assert!(index.set_claims(1, &[github.clone(), dns.clone()]));
+        assert!(index.insert(&github, 2));
+        assert!(!index.insert(&github, 2));
+        assert_eq!(index.uids(&github), &[1, 2]);
+        assert_eq!(index.uids(&dns), &[1]);
+
+        assert!(index.set_claims(1, &[github.clone()]));
+        assert!(!index.set_claims(1, &[github.clone()]));
+        assert!(index.uids(&dns).is_empty());
+        assert_eq!(index.entries.len(), 1);
+
+        assert!(index.set_claims(2, &[]));
+        assert_eq!(index.uids(&github), &[1]);
+    }
+}
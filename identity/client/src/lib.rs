@@ -1,22 +1,57 @@
+mod cache;
+mod checkpoint;
 mod claim;
 mod client;
+mod dns;
+mod encrypt;
 mod error;
+mod event;
 mod github;
+mod gitlab;
+mod https;
+mod index;
+mod message;
+mod pgp;
 mod service;
+mod ssh;
 mod subxt;
+mod team;
+mod track;
 mod utils;
 
-pub use claim::{Claim, IdentityInfo, IdentityStatus};
-pub use service::{Service, ServiceParseError};
+pub use cache::{
+    cache_config, set_cache_config, CacheConfig, CachedProof, ProofCache, StatusChange,
+};
+pub use claim::{Claim, ClaimBody, ClaimReport, ClaimStatus, IdentityInfo, IdentityStatus};
+pub use dns::DnsService;
+pub use encrypt::{EncryptedMessage, Recipient};
+pub use event::{IdentityEvent, IdentityEventSubscription};
+pub use github::GithubService;
+pub use gitlab::GitlabService;
+pub use https::HttpsService;
+pub use index::{IndexEntry, ServiceIndex};
+pub use message::UidSignature;
+pub use pgp::PgpService;
+pub use service::{
+    proof_service, register_service, ProofParams, ProofService, Service, ServiceParseError,
+};
+pub use ssh::SshService;
 pub use subxt::*;
+pub use sunshine_identity_utils::{Role, RoleParseError};
+pub use team::TeamKey;
+pub use track::{TrackSnapshot, TrackedProof};
 pub use utils::{resolve, Identifier};
 
+use async_std::task;
 use codec::Decode;
 use libipld::cache::Cache;
 use libipld::cbor::DagCborCodec;
+use libipld::cid::Cid;
 use sp_core::crypto::{Pair, Ss58Codec};
 use sp_runtime::traits::{IdentifyAccount, Verify};
 use std::convert::TryInto;
+use std::sync::Arc;
+use std::time::Duration;
 use substrate_subxt::{
     sp_core, sp_runtime, system::System, EventSubscription, Runtime, SignedExtension, SignedExtra,
 };
@@ -26,6 +61,25 @@ use sunshine_client_utils::{
     keystore, Client, Node, OffchainConfig, Result,
 };
 
+/// Result of synchronizing the keystore password with the chain.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PasswordUpdate {
+    /// The keystore password was up to date.
+    UpToDate,
+    /// The keystore password was updated.
+    Updated,
+    /// The device is further behind than the password masks kept on
+    /// chain and needs to be provisioned from another device.
+    ReprovisionRequired,
+}
+
+/// Options for proving the ownership of a service.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ProveOptions {
+    /// How long the claim is valid for. Claims don't expire by default.
+    pub expire_in: Option<Duration>,
+}
+
 #[async_trait]
 pub trait IdentityClient<N: Node>: Client<N>
 where
@@ -35,13 +89,31 @@ where
     async fn add_paperkey(&self) -> Result<Mnemonic>;
     async fn add_key(&self, key: &<N::Runtime as System>::AccountId) -> Result<()>;
     async fn remove_key(&self, key: &<N::Runtime as System>::AccountId) -> Result<()>;
+    async fn close_account(&self, beneficiary: &<N::Runtime as System>::AccountId) -> Result<bool>;
+    async fn merge_account(
+        &self,
+        from: <N::Runtime as Identity>::Uid,
+        into: <N::Runtime as Identity>::Uid,
+    ) -> Result<bool>;
+    async fn set_username(&self, username: &str) -> Result<()>;
+    async fn clear_username(&self) -> Result<()>;
+    async fn fetch_username(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Option<String>>;
+    async fn resolve_username(&self, username: &str) -> Result<<N::Runtime as Identity>::Uid>;
     async fn change_password(&self, password: &SecretString) -> Result<()>;
-    async fn update_password(&mut self) -> Result<()>;
+    async fn update_password(&mut self) -> Result<PasswordUpdate>;
     async fn subscribe_password_changes(&self) -> Result<EventSubscription<N::Runtime>>;
+    async fn subscribe_identity_events(
+        &self,
+        uid: Option<<N::Runtime as Identity>::Uid>,
+    ) -> Result<IdentityEventSubscription<N::Runtime>>;
     async fn fetch_uid(
         &self,
         key: &<N::Runtime as System>::AccountId,
     ) -> Result<Option<<N::Runtime as Identity>::Uid>>;
+    async fn follow_redirect(
+        &self,
+        uid: <N::Runtime as Identity>::Uid,
+    ) -> Result<<N::Runtime as Identity>::Uid>;
     async fn fetch_keys(
         &self,
         uid: <N::Runtime as Identity>::Uid,
@@ -52,9 +124,86 @@ where
         uid: <N::Runtime as Identity>::Uid,
     ) -> Result<<N::Runtime as Identity>::IdAccountData>;
     async fn prove_identity(&self, service: Service) -> Result<String>;
+    async fn prove_identity_with(&self, service: Service, options: ProveOptions) -> Result<String>;
     async fn revoke_identity(&self, service: Service) -> Result<()>;
+    async fn attach_proof(&self, service: Service, key: String, proof: String) -> Result<()>;
     async fn identity(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<IdentityInfo>>;
+    async fn audit_identity(&self, uid: <N::Runtime as Identity>::Uid)
+        -> Result<Vec<IdentityInfo>>;
+    async fn checkpoint(&self) -> Result<()>;
+    async fn verify_identity(&self, uid: <N::Runtime as Identity>::Uid)
+        -> Result<Vec<ClaimReport>>;
+    async fn expiring_identities(
+        &self,
+        uid: <N::Runtime as Identity>::Uid,
+        within: Duration,
+    ) -> Result<Vec<IdentityInfo>>;
     async fn resolve(&self, service: &Service) -> Result<<N::Runtime as Identity>::Uid>;
+    async fn track(&self, uid: <N::Runtime as Identity>::Uid) -> Result<()>;
+    async fn tracked_changes(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<Service>>;
+    async fn sign_as_uid(&self, msg: &[u8]) -> Result<UidSignature>;
+    async fn verify_uid_signature(&self, envelope: &UidSignature, msg: &[u8]) -> Result<()>;
+    async fn create_team(&self) -> Result<<N::Runtime as Identity>::Uid>;
+    async fn add_member(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+        member: <N::Runtime as Identity>::Uid,
+        role: Role,
+    ) -> Result<()>;
+    async fn remove_member(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+        member: <N::Runtime as Identity>::Uid,
+    ) -> Result<()>;
+    async fn members(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+    ) -> Result<Vec<(<N::Runtime as Identity>::Uid, Role)>>;
+    async fn team_key_gen(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+    ) -> Result<<N::Runtime as Identity>::Gen>;
+    async fn team_key(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+        gen: <N::Runtime as Identity>::Gen,
+        password: &SecretString,
+    ) -> Result<TeamKey>;
+    async fn rotate_team_key(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+    ) -> Result<<N::Runtime as Identity>::Gen>;
+    async fn rotate_team_keys(&self) -> Result<()>;
+    async fn set_encryption_key(&self, password: &SecretString) -> Result<()>;
+    async fn encryption_keys(
+        &self,
+        uid: <N::Runtime as Identity>::Uid,
+    ) -> Result<Vec<(<N::Runtime as System>::AccountId, Option<[u8; 32]>)>>;
+    async fn encrypt(
+        &self,
+        uid: <N::Runtime as Identity>::Uid,
+        msg: &[u8],
+    ) -> Result<EncryptedMessage>;
+    async fn decrypt(
+        &self,
+        password: &SecretString,
+        envelope: &EncryptedMessage,
+    ) -> Result<Vec<u8>>;
+    async fn attest(&self, topic: String, data_cid: Cid) -> Result<()>;
+    async fn revoke_attestation(&self, topic: &str, data_cid: &Cid) -> Result<()>;
+    async fn attestations(
+        &self,
+        uid: <N::Runtime as Identity>::Uid,
+        topic: &str,
+    ) -> Result<Vec<Claim>>;
+    async fn sign_cross_chain(&self, genesis: Vec<u8>, uid: u64) -> Result<ClaimBody>;
+    async fn prove_cross_chain(&self, link: ClaimBody) -> Result<()>;
+    async fn cross_chain_links(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<Claim>>;
+    async fn verify_cross_chain(&self, claim: &Claim) -> Result<()>;
+    async fn reverify_proofs(&self) -> Result<Vec<StatusChange>>;
+    async fn index_identity(&self, uid: <N::Runtime as Identity>::Uid) -> Result<()>;
+    async fn subscribe_identity_changes(&self) -> Result<EventSubscription<N::Runtime>>;
+    async fn proof_status_changes(&self) -> Result<Vec<StatusChange>>;
 }
 
 #[async_trait]
@@ -63,7 +212,7 @@ where
     N: Node,
     N::Runtime: Identity<Gen = u16, Mask = [u8; 32]>,
     <<<N::Runtime as Runtime>::Extra as SignedExtra<N::Runtime>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
-    <N::Runtime as System>::AccountId: Into<<N::Runtime as System>::Address> + Ss58Codec,
+    <N::Runtime as System>::AccountId: Into<<N::Runtime as System>::Address> + Ss58Codec + AsRef<[u8]>,
     <N::Runtime as Runtime>::Signature: From<<<C::KeyType as KeyType>::Pair as Pair>::Signature> + Decode,
     <<N::Runtime as Runtime>::Signature as Verify>::Signer: From<<<C::KeyType as KeyType>::Pair as Pair>::Public>
         + TryInto<<<C::KeyType as KeyType>::Pair as Pair>::Public>
@@ -73,6 +222,10 @@ where
         + Sync,
     C: Client<N, KeyType = K, Keystore = keystore::Keystore<K>>,
     C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, Claim>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ProofCache>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, ServiceIndex>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, TrackSnapshot>,
+    C::OffchainClient: Cache<OffchainConfig<N>, DagCborCodec, EncryptedMessage>,
     K: KeyType + 'static,
 {
     async fn create_account_for(&self, key: &<N::Runtime as System>::AccountId) -> Result<()> {
@@ -91,11 +244,39 @@ where
         client::remove_key(self, key).await
     }
 
+    async fn close_account(&self, beneficiary: &<N::Runtime as System>::AccountId) -> Result<bool> {
+        client::close_account(self, beneficiary).await
+    }
+
+    async fn merge_account(
+        &self,
+        from: <N::Runtime as Identity>::Uid,
+        into: <N::Runtime as Identity>::Uid,
+    ) -> Result<bool> {
+        client::merge_account(self, from, into).await
+    }
+
+    async fn set_username(&self, username: &str) -> Result<()> {
+        client::set_username(self, username).await
+    }
+
+    async fn clear_username(&self) -> Result<()> {
+        client::clear_username(self).await
+    }
+
+    async fn fetch_username(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Option<String>> {
+        client::fetch_username(self, uid).await
+    }
+
+    async fn resolve_username(&self, username: &str) -> Result<<N::Runtime as Identity>::Uid> {
+        client::resolve_username(self, username).await
+    }
+
     async fn change_password(&self, password: &SecretString) -> Result<()> {
         client::change_password(self, password).await
     }
 
-    async fn update_password(&mut self) -> Result<()> {
+    async fn update_password(&mut self) -> Result<PasswordUpdate> {
         client::update_password(self).await
     }
 
@@ -103,10 +284,21 @@ where
         client::subscribe_password_changes(self).await
     }
 
+    async fn subscribe_identity_events(
+        &self,
+        uid: Option<<N::Runtime as Identity>::Uid>,
+    ) -> Result<IdentityEventSubscription<N::Runtime>> {
+        client::subscribe_identity_events(self, uid).await
+    }
+
     async fn fetch_uid(&self, key: &<N::Runtime as System>::AccountId) -> Result<Option<<N::Runtime as Identity>::Uid>> {
         client::fetch_uid(self, key).await
     }
 
+    async fn follow_redirect(&self, uid: <N::Runtime as Identity>::Uid) -> Result<<N::Runtime as Identity>::Uid> {
+        client::follow_redirect(self, uid).await
+    }
+
     async fn fetch_keys(
         &self,
         uid: <N::Runtime as Identity>::Uid,
@@ -120,18 +312,238 @@ where
     }
 
     async fn prove_identity(&self, service: Service) -> Result<String> {
-        client::prove_identity(self, service).await
+        client::prove_identity_with(self, service, Default::default()).await
+    }
+
+    async fn prove_identity_with(&self, service: Service, options: ProveOptions) -> Result<String> {
+        client::prove_identity_with(self, service, options).await
     }
 
     async fn revoke_identity(&self, service: Service) -> Result<()> {
         client::revoke_identity(self, service).await
     }
 
+    async fn attach_proof(&self, service: Service, key: String, proof: String) -> Result<()> {
+        client::attach_proof(self, service, key, proof).await
+    }
+
     async fn identity(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<IdentityInfo>> {
         client::identity(self, uid).await
     }
 
+    async fn audit_identity(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<IdentityInfo>> {
+        client::audit_identity(self, uid).await
+    }
+
+    async fn checkpoint(&self) -> Result<()> {
+        client::checkpoint(self).await
+    }
+
+    async fn verify_identity(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<ClaimReport>> {
+        client::verify_identity(self, uid).await
+    }
+
+    async fn expiring_identities(
+        &self,
+        uid: <N::Runtime as Identity>::Uid,
+        within: Duration,
+    ) -> Result<Vec<IdentityInfo>> {
+        client::expiring_identities(self, uid, within).await
+    }
+
     async fn resolve(&self, service: &Service) -> Result<<N::Runtime as Identity>::Uid> {
         client::resolve(self, service).await
     }
+
+    async fn track(&self, uid: <N::Runtime as Identity>::Uid) -> Result<()> {
+        client::track(self, uid).await
+    }
+
+    async fn tracked_changes(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<Service>> {
+        client::tracked_changes(self, uid).await
+    }
+
+    async fn sign_as_uid(&self, msg: &[u8]) -> Result<UidSignature> {
+        client::sign_as_uid(self, msg).await
+    }
+
+    async fn verify_uid_signature(&self, envelope: &UidSignature, msg: &[u8]) -> Result<()> {
+        client::verify_uid_signature(self, envelope, msg).await
+    }
+
+    async fn create_team(&self) -> Result<<N::Runtime as Identity>::Uid> {
+        client::create_team(self).await
+    }
+
+    async fn add_member(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+        member: <N::Runtime as Identity>::Uid,
+        role: Role,
+    ) -> Result<()> {
+        client::add_member(self, team, member, role).await
+    }
+
+    async fn remove_member(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+        member: <N::Runtime as Identity>::Uid,
+    ) -> Result<()> {
+        client::remove_member(self, team, member).await
+    }
+
+    async fn members(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+    ) -> Result<Vec<(<N::Runtime as Identity>::Uid, Role)>> {
+        client::members(self, team).await
+    }
+
+    async fn team_key_gen(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+    ) -> Result<<N::Runtime as Identity>::Gen> {
+        client::team_key_gen(self, team).await
+    }
+
+    async fn team_key(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+        gen: <N::Runtime as Identity>::Gen,
+        password: &SecretString,
+    ) -> Result<TeamKey> {
+        client::team_key(self, team, gen, password).await
+    }
+
+    async fn rotate_team_key(
+        &self,
+        team: <N::Runtime as Identity>::Uid,
+    ) -> Result<<N::Runtime as Identity>::Gen> {
+        client::rotate_team_key(self, team).await
+    }
+
+    async fn rotate_team_keys(&self) -> Result<()> {
+        client::rotate_team_keys(self).await
+    }
+
+    async fn set_encryption_key(&self, password: &SecretString) -> Result<()> {
+        client::set_encryption_key(self, password).await
+    }
+
+    async fn encryption_keys(
+        &self,
+        uid: <N::Runtime as Identity>::Uid,
+    ) -> Result<Vec<(<N::Runtime as System>::AccountId, Option<[u8; 32]>)>> {
+        client::encryption_keys(self, uid).await
+    }
+
+    async fn encrypt(&self, uid: <N::Runtime as Identity>::Uid, msg: &[u8]) -> Result<EncryptedMessage> {
+        client::encrypt(self, uid, msg).await
+    }
+
+    async fn decrypt(&self, password: &SecretString, envelope: &EncryptedMessage) -> Result<Vec<u8>> {
+        client::decrypt(self, password, envelope).await
+    }
+
+    async fn attest(&self, topic: String, data_cid: Cid) -> Result<()> {
+        client::attest(self, topic, data_cid).await
+    }
+
+    async fn revoke_attestation(&self, topic: &str, data_cid: &Cid) -> Result<()> {
+        client::revoke_attestation(self, topic, data_cid).await
+    }
+
+    async fn attestations(&self, uid: <N::Runtime as Identity>::Uid, topic: &str) -> Result<Vec<Claim>> {
+        client::attestations(self, uid, topic).await
+    }
+
+    async fn sign_cross_chain(&self, genesis: Vec<u8>, uid: u64) -> Result<ClaimBody> {
+        client::sign_cross_chain(self, genesis, uid).await
+    }
+
+    async fn prove_cross_chain(&self, link: ClaimBody) -> Result<()> {
+        client::prove_cross_chain(self, link).await
+    }
+
+    async fn cross_chain_links(&self, uid: <N::Runtime as Identity>::Uid) -> Result<Vec<Claim>> {
+        client::cross_chain_links(self, uid).await
+    }
+
+    async fn verify_cross_chain(&self, claim: &Claim) -> Result<()> {
+        client::verify_cross_chain(self, claim).await
+    }
+
+    async fn reverify_proofs(&self) -> Result<Vec<StatusChange>> {
+        client::reverify_proofs(self).await
+    }
+
+    async fn proof_status_changes(&self) -> Result<Vec<StatusChange>> {
+        client::proof_status_changes(self).await
+    }
+
+    async fn index_identity(&self, uid: <N::Runtime as Identity>::Uid) -> Result<()> {
+        client::index_identity(self, uid).await
+    }
+
+    async fn subscribe_identity_changes(&self) -> Result<EventSubscription<N::Runtime>> {
+        client::subscribe_identity_changes(self).await
+    }
+}
+
+/// Spawns a task that keeps the service index up to date with the
+/// identity changes on chain.
+///
+/// Meant for long running services that share the client behind an `Arc`,
+/// like a resolver. The cli doesn't start it, because its `run` command
+/// needs exclusive access to the client to update the password. Without
+/// it `resolve` falls back to asking the proof services and indexes the
+/// uids it finds.
+pub fn spawn_indexer<N, C>(client: Arc<C>) -> task::JoinHandle<Result<()>>
+where
+    N: Node,
+    N::Runtime: Identity,
+    C: IdentityClient<N> + 'static,
+{
+    task::spawn(async move {
+        let mut subscription = client.subscribe_identity_changes().await?;
+        while let Some(event) = subscription.next().await {
+            let event = IdentityChangedEvent::<N::Runtime>::decode(&mut &event?.data[..])?;
+            if let Err(err) = client.index_identity(event.uid).await {
+                log::warn!("failed to index identity {}: {}", event.uid, err);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Spawns a task that re-verifies proofs with expired verification results
+/// every `interval` and logs the status changes.
+///
+/// Like [`spawn_indexer`] it is started by services embedding the client.
+/// Without it expired results are verified again when an identity is
+/// looked up.
+pub fn spawn_reverifier<N, C>(client: Arc<C>, interval: Duration) -> task::JoinHandle<()>
+where
+    N: Node,
+    N::Runtime: Identity,
+    C: IdentityClient<N> + 'static,
+{
+    task::spawn(async move {
+        loop {
+            match client.reverify_proofs().await {
+                Ok(changes) => {
+                    for change in changes {
+                        log::info!(
+                            "proof of {} changed from {:?} to {:?}",
+                            change.service,
+                            change.from,
+                            change.to
+                        );
+                    }
+                }
+                Err(err) => log::warn!("failed to reverify proofs: {}", err),
+            }
+            task::sleep(interval).await;
+        }
+    })
 }
@@ -0,0 +1,61 @@
+use libipld::cbor::DagCborCodec;
+use libipld::codec::Codec as _;
+use libipld::multibase::{decode, encode, Base};
+use libipld::DagCbor;
+use sunshine_client_utils::Result;
+
+/// Prefix of signed messages, so a message signature can't be passed off
+/// as a claim signature.
+const MESSAGE_PREFIX: &[u8] = b"sunshine-identity-message\n";
+
+/// Returns the bytes that are signed for a message.
+pub(crate) fn signed_message(msg: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MESSAGE_PREFIX.len() + msg.len());
+    bytes.extend_from_slice(MESSAGE_PREFIX);
+    bytes.extend_from_slice(msg);
+    bytes
+}
+
+/// A detached signature of a message by a uid.
+#[derive(Clone, Debug, Eq, PartialEq, DagCbor)]
+pub struct UidSignature {
+    /// The user that signed the message.
+    pub uid: u64,
+    /// The public key that signed the message.
+    pub public: String,
+    /// The block at which the key was a key of the user.
+    pub block: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl UidSignature {
+    /// Encodes the envelope as multibase.
+    pub fn encode(&self) -> Result<String> {
+        Ok(encode(Base::Base64, DagCborCodec.encode(self)?))
+    }
+
+    /// Decodes an envelope encoded with `encode`.
+    pub fn decode(s: &str) -> Result<Self> {
+        let (_, bytes) = decode(s.trim())?;
+        Ok(DagCborCodec.decode(&bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_envelope() {
+        let envelope = UidSignature {
+            uid: 42,
+            public: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".into(),
+            block: vec![1; 32],
+            signature: vec![2; 65],
+        };
+        let encoded = envelope.encode().unwrap();
+        assert_eq!(UidSignature::decode(&encoded).unwrap(), envelope);
+        assert!(UidSignature::decode("not an envelope").is_err());
+        assert!(signed_message(b"msg").ends_with(b"\nmsg"));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::claim::Claim;
+use crate::error::ProofNotFound;
+use crate::service::{contains_signature, ProofParams, ProofService};
+use pgp::cleartext::CleartextSignedMessage;
+use pgp::{Deserializable, SignedPublicKey};
+use sunshine_client_utils::{async_trait, Result};
+
+/// Proofs cleartext signed with a pgp key.
+///
+/// The signed statement and the armored public key are published to the
+/// offchain store, so the proof can be verified without network access.
+pub struct PgpService;
+
+/// Returns the fingerprint of a key in upper case hex.
+fn fingerprint(key: &SignedPublicKey) -> String {
+    key.fingerprint()
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+#[async_trait]
+impl ProofService for PgpService {
+    fn name(&self) -> &str {
+        "pgp"
+    }
+
+    fn validate(&self, fingerprint: &str) -> bool {
+        fingerprint.len() == 40 && fingerprint.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    async fn verify(&self, _fingerprint: &str, _claim: &Claim) -> Result<String> {
+        Err(ProofNotFound.into())
+    }
+
+    async fn resolve(&self, _fingerprint: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    fn verify_attached(
+        &self,
+        username: &str,
+        signature: &str,
+        key: &str,
+        proof: &str,
+    ) -> Result<String> {
+        let (key, _) = SignedPublicKey::from_string(key)?;
+        key.verify()?;
+        let fp = fingerprint(&key);
+        if !fp.eq_ignore_ascii_case(username) {
+            return Err(ProofNotFound.into());
+        }
+        let (msg, _) = CleartextSignedMessage::from_string(proof)?;
+        let signed = msg.verify(&key).is_ok()
+            || key
+                .public_subkeys
+                .iter()
+                .any(|subkey| msg.verify(subkey).is_ok());
+        if !signed {
+            return Err(ProofNotFound.into());
+        }
+        let text = msg.text();
+        if !text.contains(&fp) || !contains_signature(&text, signature) {
+            return Err(ProofNotFound.into());
+        }
+        Ok(format!("openpgp4fpr:{}", fp))
+    }
+
+    fn proof(&self, params: &ProofParams<'_>) -> String {
+        format!(
+            include_str!("../pgp-proof-template.md"),
+            genesis = params.genesis,
+            block = params.block,
+            uid = params.uid,
+            fingerprint = params.username.to_uppercase(),
+            public = params.public,
+            signature = params.signature,
+        )
+    }
+
+    fn cli_instructions(&self, fingerprint: &str) -> String {
+        format!(
+            include_str!("../pgp-proof-instructions.md"),
+            fingerprint = fingerprint,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str = "5F44B9D178466965E7E93D112E17A1FD206B507B";
+    const SIGNATURE: &str = "mc2lnbmF0dXJl";
+    const KEY: &str = include_str!("../fixtures/pgp-key.asc");
+    const PROOF: &str = include_str!("../fixtures/pgp-proof.asc");
+    const SUBKEY_PROOF: &str = include_str!("../fixtures/pgp-subkey-proof.asc");
+    const OTHER_PROOF: &str = include_str!("../fixtures/pgp-other-proof.asc");
+
+    #[test]
+    fn test_validate_fingerprint() {
+        let service = PgpService;
+        assert!(service.validate("0123456789ABCDEF0123456789abcdef01234567"));
+        assert!(!service.validate("0123456789ABCDEF"));
+        assert!(!service.validate("0123456789ABCDEF0123456789abcdef0123456g"));
+    }
+
+    #[test]
+    fn test_reject_invalid_key() {
+        let fp = "0123456789ABCDEF0123456789ABCDEF01234567";
+        assert!(PgpService
+            .verify_attached(fp, "signature", "not a key", "not a proof")
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_attached() {
+        let url = PgpService
+            .verify_attached(FINGERPRINT, SIGNATURE, KEY, PROOF)
+            .unwrap();
+        assert_eq!(url, format!("openpgp4fpr:{}", FINGERPRINT));
+        let fp = FINGERPRINT.to_lowercase();
+        assert!(PgpService
+            .verify_attached(&fp, SIGNATURE, KEY, PROOF)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_verify_subkey_signature() {
+        assert!(PgpService
+            .verify_attached(FINGERPRINT, SIGNATURE, KEY, SUBKEY_PROOF)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_reject_wrong_fingerprint() {
+        let other = "83AFFF45A94057C552775FB47C2FC010D072D25E";
+        assert!(PgpService
+            .verify_attached(other, SIGNATURE, KEY, PROOF)
+            .is_err());
+        assert!(PgpService
+            .verify_attached(FINGERPRINT, "mb3RoZXI", KEY, PROOF)
+            .is_err());
+    }
+
+    #[test]
+    fn test_reject_wrong_signature() {
+        let tampered = PROOF.replace("I am 0 on", "I am 1 on");
+        assert!(PgpService
+            .verify_attached(FINGERPRINT, SIGNATURE, KEY, &tampered)
+            .is_err());
+        assert!(PgpService
+            .verify_attached(FINGERPRINT, SIGNATURE, KEY, OTHER_PROOF)
+            .is_err());
+    }
+}
@@ -1,68 +1,253 @@
 use crate::claim::Claim;
-use crate::github;
+use crate::dns::DnsService;
+use crate::error::ProofNotFound;
+use crate::github::GithubService;
+use crate::gitlab::GitlabService;
+use crate::https::HttpsService;
+use crate::pgp::PgpService;
+use crate::ssh::SshService;
+use async_std::task;
 use core::str::FromStr;
 use libipld::cbor::DagCborCodec;
 use libipld::codec::Codec;
 use libipld::json::DagJsonCodec;
 use libipld::multibase::{encode, Base};
 use libipld::{DagCbor, Ipld};
-use sunshine_client_utils::Result;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use sunshine_client_utils::{async_trait, Result};
 use thiserror::Error;
 
+/// The values that are rendered into a proof.
+pub struct ProofParams<'a> {
+    pub genesis: String,
+    pub block: String,
+    pub uid: String,
+    pub username: &'a str,
+    pub public: &'a str,
+    /// The signed claim encoded as DAG-JSON.
+    pub object: String,
+    pub signature: String,
+}
+
+/// A service that users can prove ownership of an account on.
+#[async_trait]
+pub trait ProofService: Send + Sync {
+    /// The name of the service used in `username@service`.
+    fn name(&self) -> &str;
+
+    /// Checks that the username is valid for the service.
+    fn validate(&self, username: &str) -> bool {
+        !username.is_empty()
+    }
+
+    /// Finds a proof of the claim and returns its url.
+    async fn verify(&self, username: &str, claim: &Claim) -> Result<String>;
+
+    /// Returns the uids claimed by the proofs of a user.
+    async fn resolve(&self, username: &str) -> Result<Vec<String>>;
+
+    /// Verifies a proof that was published to the offchain store together
+    /// with the key that signed it and returns its url.
+    fn verify_attached(
+        &self,
+        _username: &str,
+        _signature: &str,
+        _key: &str,
+        _proof: &str,
+    ) -> Result<String> {
+        Err(ProofNotFound.into())
+    }
+
+    /// Renders the proof.
+    fn proof(&self, params: &ProofParams<'_>) -> String;
+
+    /// Instructions for publishing the proof.
+    fn cli_instructions(&self, username: &str) -> String;
+}
+
+static SERVICES: Lazy<RwLock<HashMap<String, Arc<dyn ProofService>>>> = Lazy::new(|| {
+    let mut services = HashMap::new();
+    let defaults: Vec<Arc<dyn ProofService>> = vec![
+        Arc::new(GithubService::default()),
+        Arc::new(GitlabService::default()),
+        Arc::new(HttpsService::default()),
+        Arc::new(DnsService::default()),
+        Arc::new(PgpService),
+        Arc::new(SshService),
+    ];
+    for service in defaults {
+        services.insert(service.name().to_string(), service);
+    }
+    RwLock::new(services)
+});
+
+/// Registers a proof service, replacing any service with the same name.
+pub fn register_service<S: ProofService + 'static>(service: S) {
+    SERVICES
+        .write()
+        .unwrap()
+        .insert(service.name().to_string(), Arc::new(service));
+}
+
+/// Returns the proof service with `name`.
+pub fn proof_service(name: &str) -> Option<Arc<dyn ProofService>> {
+    SERVICES.read().unwrap().get(name).cloned()
+}
+
+/// Renders a proof that is published as a markdown document. `account`
+/// describes the account on the service and `publication` how the proof
+/// is published.
+pub fn proof_markdown(params: &ProofParams<'_>, account: &str, publication: &str) -> String {
+    format!(
+        include_str!("../proof-template.md"),
+        account = account,
+        genesis = params.genesis,
+        block = params.block,
+        uid = params.uid,
+        public = params.public,
+        object = params.object,
+        signature = params.signature,
+        publication = publication,
+    )
+}
+
+/// Returns `true` if the proof contains the signature on a line by itself.
+pub fn contains_signature(content: &str, signature: &str) -> bool {
+    content.lines().any(|line| line.trim() == signature)
+}
+
+/// Returns the signature of a claim as it appears in proofs.
+pub fn encode_signature(claim: &Claim) -> String {
+    encode(Base::Base64, claim.signature())
+}
+
+/// Encodes a claim as the DAG-JSON object embedded in proofs.
+pub fn encode_proof(claim: &Claim) -> Result<String> {
+    let bytes = DagCborCodec.encode(claim)?;
+    let ipld: Ipld = DagCborCodec.decode(&bytes)?;
+    let bytes = DagJsonCodec.encode(&ipld)?;
+    Ok(String::from_utf8(bytes).expect("json codec returns valid utf8"))
+}
+
+fn decode_proof(json: &str) -> Result<Claim> {
+    let ipld: Ipld = DagJsonCodec.decode(json.trim().as_bytes())?;
+    let bytes = DagCborCodec.encode(&ipld)?;
+    Ok(DagCborCodec.decode(&bytes)?)
+}
+
+/// Extracts the claim from the first fenced code block of a proof that
+/// contains a DAG-JSON encoded claim.
+pub fn parse_proof(content: &str) -> Option<Claim> {
+    let mut block: Option<Vec<&str>> = None;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            if let Some(lines) = block.take() {
+                if let Ok(claim) = decode_proof(&lines.join("\n")) {
+                    return Some(claim);
+                }
+            } else {
+                block = Some(Vec::new());
+            }
+        } else if let Some(lines) = block.as_mut() {
+            lines.push(line);
+        }
+    }
+    None
+}
+
+/// Returns `true` if the string is a fully qualified domain name.
+pub fn is_domain(domain: &str) -> bool {
+    domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() < 64
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Fetches the body of a url.
+pub(crate) async fn fetch(url: String) -> Result<String> {
+    let res = task::spawn_blocking(move || ureq::get(&url).call()).await;
+    if !res.ok() {
+        return Err(ProofNotFound.into());
+    }
+    Ok(res.into_string()?)
+}
+
+/// An account on a proof service.
+///
+/// Github accounts keep the encoding they had before proof services were
+/// pluggable, so that existing claims still decode. Use `Service::new` to
+/// create a service.
 #[derive(Clone, Debug, Eq, PartialEq, Hash, DagCbor)]
 pub enum Service {
     Github(String),
+    Other { service: String, username: String },
 }
 
 impl Service {
+    pub fn new(service: &str, username: &str) -> Self {
+        if service == "github" {
+            Self::Github(username.into())
+        } else {
+            Self::Other {
+                service: service.into(),
+                username: username.into(),
+            }
+        }
+    }
+
     pub fn username(&self) -> &str {
         match self {
-            Self::Github(username) => &username,
+            Self::Github(username) => username,
+            Self::Other { username, .. } => username,
         }
     }
 
     pub fn service(&self) -> &str {
         match self {
             Self::Github(_) => "github",
+            Self::Other { service, .. } => service,
         }
     }
 
-    pub async fn verify(&self, signature: &[u8]) -> Result<String> {
-        let signature = encode(Base::Base64, signature);
-        match self {
-            Self::Github(user) => github::verify(&user, &signature).await,
-        }
+    fn proof_service(&self) -> Result<Arc<dyn ProofService>> {
+        Ok(proof_service(self.service())
+            .ok_or_else(|| ServiceParseError::Unknown(self.service().into()))?)
+    }
+
+    pub async fn verify(&self, claim: &Claim) -> Result<String> {
+        self.proof_service()?.verify(self.username(), claim).await
+    }
+
+    pub fn verify_attached(&self, claim: &Claim, key: &str, proof: &str) -> Result<String> {
+        self.proof_service()?
+            .verify_attached(self.username(), &encode_signature(claim), key, proof)
     }
 
     pub async fn resolve(&self) -> Result<Vec<String>> {
-        match self {
-            Self::Github(user) => github::resolve(&user).await,
-        }
+        self.proof_service()?.resolve(self.username()).await
     }
 
     pub fn proof(&self, claim: &Claim) -> Result<String> {
-        let genesis = encode(Base::Base64, &claim.claim().genesis);
-        let block = encode(Base::Base64, &claim.claim().block);
-        let uid = claim.claim().uid.to_string();
-        let public = &claim.claim().public;
-        let signature = encode(Base::Base64, claim.signature());
-
-        let bytes = DagCborCodec.encode(claim.claim())?;
-        let ipld: Ipld = DagCborCodec.decode(&bytes)?;
-        let bytes = DagJsonCodec.encode(&ipld)?;
-        let object = std::str::from_utf8(&bytes).expect("json codec returns valid utf8");
-
-        Ok(match self {
-            Self::Github(user) => {
-                github::proof(&genesis, &block, &uid, &user, &public, &object, &signature)
-            }
-        })
+        let params = ProofParams {
+            genesis: encode(Base::Base64, &claim.claim().genesis),
+            block: encode(Base::Base64, &claim.claim().block),
+            uid: claim.claim().uid.to_string(),
+            username: self.username(),
+            public: &claim.claim().public,
+            object: encode_proof(claim)?,
+            signature: encode_signature(claim),
+        };
+        Ok(self.proof_service()?.proof(&params))
     }
 
-    pub fn cli_instructions(&self) -> String {
-        match self {
-            Self::Github(_) => github::cli_instructions(),
-        }
+    pub fn cli_instructions(&self) -> Result<String> {
+        Ok(self.proof_service()?.cli_instructions(self.username()))
     }
 }
 
@@ -88,10 +273,12 @@ impl FromStr for Service {
         if parts.next().is_some() {
             return Err(ServiceParseError::Invalid);
         }
-        match service {
-            "github" => Ok(Self::Github(username.into())),
-            _ => Err(ServiceParseError::Unknown(service.into())),
+        let proof_service =
+            proof_service(service).ok_or_else(|| ServiceParseError::Unknown(service.into()))?;
+        if !proof_service.validate(username) {
+            return Err(ServiceParseError::Invalid);
         }
+        Ok(Self::new(service, username))
     }
 }
 
@@ -102,3 +289,144 @@ pub enum ServiceParseError {
     #[error("Unknown service '{0}'")]
     Unknown(String),
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::claim::{ClaimBody, UnsignedClaim};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serves the routes returned by `routes` on a local port and returns
+    /// the base url of the server.
+    pub fn serve(routes: impl FnOnce(&str) -> Vec<(String, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let routes = routes(&base);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = if let Ok(stream) = stream {
+                    stream
+                } else {
+                    break;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                reader.read_line(&mut request).ok();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).ok();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                }
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let (status, body) = match routes.iter().find(|(p, _)| p == path) {
+                    Some((_, body)) => ("200 OK", body.as_str()),
+                    None => ("404 Not Found", ""),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .ok();
+            }
+        });
+        base
+    }
+
+    /// Returns an ownership claim of `service` made by uid 42.
+    pub fn claim(service: &Service, signature: &[u8]) -> Claim {
+        let claim = UnsignedClaim {
+            genesis: b"genesis".to_vec(),
+            block: b"block".to_vec(),
+            uid: 42,
+            public: "public".into(),
+            prev: None,
+            seqno: 1,
+            ctime: 0,
+            expire_in: u64::MAX,
+            body: ClaimBody::Ownership(service.clone()),
+        };
+        Claim::new(claim, signature.to_vec())
+    }
+
+    /// Serves the proof of `service` on the routes returned by `routes`
+    /// and checks that the proof service created by `new` finds it at the
+    /// url returned by `url`. The closures get the base url of the server.
+    pub async fn test_proof_service<S: ProofService>(
+        service: &Service,
+        routes: impl FnOnce(&str, String) -> Vec<(String, String)>,
+        new: impl FnOnce(&str) -> S,
+        url: impl FnOnce(&str) -> String,
+    ) {
+        let other = claim(service, b"other");
+        let signed = claim(service, b"signature");
+        let proof = service.proof(&signed).unwrap();
+        let base = serve(|base| routes(base, proof));
+        let proof_service = new(&base);
+        let username = service.username();
+        assert_eq!(
+            proof_service.verify(username, &signed).await.unwrap(),
+            url(&base)
+        );
+        assert!(proof_service.verify(username, &other).await.is_err());
+        let uids = proof_service.resolve(username).await.unwrap();
+        assert_eq!(uids, vec!["42".to_string()]);
+    }
+
+    #[test]
+    fn parse_proof_layout() {
+        let service = Service::new("github", "dvc94ch");
+        let claim = claim(&service, b"signature");
+        let proof = service.proof(&claim).unwrap();
+        assert_eq!(parse_proof(&proof), Some(claim.clone()));
+
+        let reformatted = proof
+            .lines()
+            .map(|line| format!("  {}  ", line))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        assert_eq!(parse_proof(&reformatted), Some(claim));
+        assert_eq!(parse_proof("no proof"), None);
+    }
+
+    #[test]
+    fn legacy_service_encoding() {
+        /// The encoding of services before proof services were pluggable.
+        #[derive(DagCbor)]
+        enum LegacyService {
+            Github(String),
+        }
+
+        let legacy = DagCborCodec
+            .encode(&LegacyService::Github("dvc94ch".into()))
+            .unwrap();
+        let service = Service::new("github", "dvc94ch");
+        assert_eq!(DagCborCodec.encode(&service).unwrap(), legacy);
+        assert_eq!(DagCborCodec.decode::<Service>(&legacy).unwrap(), service);
+
+        let service = Service::new("gitlab", "dvc94ch");
+        let bytes = DagCborCodec.encode(&service).unwrap();
+        assert_eq!(DagCborCodec.decode::<Service>(&bytes).unwrap(), service);
+    }
+
+    #[test]
+    fn parse_service() {
+        assert_eq!(
+            Service::from_str("dvc94ch@github"),
+            Ok(Service::new("github", "dvc94ch"))
+        );
+        assert_eq!(
+            Service::from_str("dvc94ch@twitter"),
+            Err(ServiceParseError::Unknown("twitter".into()))
+        );
+        assert_eq!(
+            Service::from_str("@github"),
+            Err(ServiceParseError::Invalid)
+        );
+    }
+}
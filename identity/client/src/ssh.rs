@@ -0,0 +1,240 @@
+use crate::claim::Claim;
+use crate::error::ProofNotFound;
+use crate::service::{ProofParams, ProofService};
+use core::convert::TryFrom;
+use ed25519_dalek::{PublicKey, Signature};
+use sha2::{Digest, Sha256, Sha512};
+use sunshine_client_utils::{async_trait, Result};
+
+/// The namespace that proofs are signed in.
+const NAMESPACE: &str = "sunshine-identity";
+
+/// The only supported key type.
+const KEY_TYPE: &str = "ssh-ed25519";
+
+/// Magic preamble of the sshsig format.
+const MAGIC: &[u8] = b"SSHSIG";
+
+const BEGIN: &str = "-----BEGIN SSH SIGNATURE-----";
+const END: &str = "-----END SSH SIGNATURE-----";
+
+/// Proofs signed with an ed25519 ssh key in the sshsig format.
+///
+/// The username is the `SHA256:` fingerprint of the key. The public key and
+/// the signature over the claim signature are published to the offchain
+/// store.
+///
+/// The key signs the claim signature instead of the DAG-CBOR encoded claim,
+/// so that the proof is a line of text that can be signed with `ssh-keygen`.
+/// Claims are only passed to the service after their signature was checked
+/// against the claim, and a valid signature commits to exactly one claim, so
+/// signing it binds the ssh key to the claim.
+pub struct SshService;
+
+/// The message that is signed, which is the claim signature on a line.
+fn message(signature: &str) -> String {
+    format!("{}\n", signature)
+}
+
+/// Reads the length prefixed fields of the ssh wire format.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn u32(&mut self) -> Option<u32> {
+        if self.0.len() < 4 {
+            return None;
+        }
+        let (int, rest) = self.0.split_at(4);
+        self.0 = rest;
+        Some(u32::from_be_bytes([int[0], int[1], int[2], int[3]]))
+    }
+
+    fn string(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        if self.0.len() < len {
+            return None;
+        }
+        let (string, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(string)
+    }
+}
+
+fn write_string(buf: &mut Vec<u8>, string: &[u8]) {
+    buf.extend_from_slice(&(string.len() as u32).to_be_bytes());
+    buf.extend_from_slice(string);
+}
+
+/// Parses a public key in `authorized_keys` format and returns its wire
+/// encoding and the ed25519 key.
+fn parse_key(key: &str) -> Option<(Vec<u8>, PublicKey)> {
+    let mut parts = key.split_whitespace();
+    if parts.next()? != KEY_TYPE {
+        return None;
+    }
+    let blob = base64::decode(parts.next()?).ok()?;
+    let mut reader = Reader(&blob);
+    if reader.string()? != KEY_TYPE.as_bytes() {
+        return None;
+    }
+    let key = PublicKey::from_bytes(reader.string()?).ok()?;
+    if !reader.0.is_empty() {
+        return None;
+    }
+    Some((blob, key))
+}
+
+/// Returns the `SHA256:` fingerprint of the wire encoding of a key.
+fn fingerprint(blob: &[u8]) -> String {
+    let hash = Sha256::digest(blob);
+    format!(
+        "SHA256:{}",
+        base64::encode_config(&hash, base64::STANDARD_NO_PAD)
+    )
+}
+
+/// Verifies an armored sshsig of `msg` made by `key` in `namespace`.
+fn verify_sshsig(
+    blob: &[u8],
+    key: &PublicKey,
+    namespace: &str,
+    msg: &[u8],
+    sig: &str,
+) -> Option<()> {
+    let armored = sig.trim().strip_prefix(BEGIN)?.strip_suffix(END)?;
+    let armored: String = armored.split_whitespace().collect();
+    let sig = base64::decode(&armored).ok()?;
+    if !sig.starts_with(MAGIC) {
+        return None;
+    }
+    let mut reader = Reader(&sig[MAGIC.len()..]);
+    if reader.u32()? != 1 || reader.string()? != blob {
+        return None;
+    }
+    if reader.string()? != namespace.as_bytes() {
+        return None;
+    }
+    let reserved = reader.string()?;
+    let hash_alg = reader.string()?;
+    let mut signature = Reader(reader.string()?);
+    if !reader.0.is_empty() || signature.string()? != KEY_TYPE.as_bytes() {
+        return None;
+    }
+    let signature = Signature::try_from(signature.string()?).ok()?;
+    let hash = match hash_alg {
+        b"sha256" => Sha256::digest(msg).to_vec(),
+        b"sha512" => Sha512::digest(msg).to_vec(),
+        _ => return None,
+    };
+    let mut data = MAGIC.to_vec();
+    write_string(&mut data, namespace.as_bytes());
+    write_string(&mut data, reserved);
+    write_string(&mut data, hash_alg);
+    write_string(&mut data, &hash);
+    key.verify_strict(&data, &signature).ok()
+}
+
+#[async_trait]
+impl ProofService for SshService {
+    fn name(&self) -> &str {
+        "ssh"
+    }
+
+    fn validate(&self, fingerprint: &str) -> bool {
+        fingerprint
+            .strip_prefix("SHA256:")
+            .and_then(|hash| base64::decode_config(hash, base64::STANDARD_NO_PAD).ok())
+            .map(|hash| hash.len() == 32)
+            .unwrap_or_default()
+    }
+
+    async fn verify(&self, _fingerprint: &str, _claim: &Claim) -> Result<String> {
+        Err(ProofNotFound.into())
+    }
+
+    async fn resolve(&self, _fingerprint: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    fn verify_attached(
+        &self,
+        username: &str,
+        signature: &str,
+        key: &str,
+        proof: &str,
+    ) -> Result<String> {
+        let (blob, key) = parse_key(key).ok_or(ProofNotFound)?;
+        let fp = fingerprint(&blob);
+        if fp != username {
+            return Err(ProofNotFound.into());
+        }
+        let msg = message(signature);
+        verify_sshsig(&blob, &key, NAMESPACE, msg.as_bytes(), proof).ok_or(ProofNotFound)?;
+        Ok(format!("ssh:{}", fp))
+    }
+
+    fn proof(&self, params: &ProofParams<'_>) -> String {
+        message(&params.signature)
+    }
+
+    fn cli_instructions(&self, _fingerprint: &str) -> String {
+        format!(
+            include_str!("../ssh-proof-instructions.md"),
+            namespace = NAMESPACE,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claim::{IdentityInfo, IdentityStatus};
+    use crate::service::Service;
+
+    const FINGERPRINT: &str = "SHA256:rx4FHusTLRiRNrNxglR6acNF45Vv0Ap1ms/JeD72eXY";
+    const SIGNATURE: &str = "mc2lnbmF0dXJl";
+    const KEY: &str = include_str!("../fixtures/ssh-key.pub");
+    const PROOF: &str = include_str!("../fixtures/ssh-proof.sig");
+    const NAMESPACE_PROOF: &str = include_str!("../fixtures/ssh-namespace-proof.sig");
+
+    #[test]
+    fn test_validate_fingerprint() {
+        let service = SshService;
+        assert!(service.validate("SHA256:WvUR6hRuXhDIM3PjMYnQ1BvPHz9vf5WKqS1qS3Fs0kg"));
+        assert!(!service.validate("MD5:WvUR6hRuXhDIM3PjMYnQ1BvPHz9vf5WKqS1qS3Fs0kg"));
+        assert!(!service.validate("dvc94ch"));
+    }
+
+    #[test]
+    fn test_verify_attached() {
+        let url = SshService
+            .verify_attached(FINGERPRINT, SIGNATURE, KEY, PROOF)
+            .unwrap();
+        assert_eq!(url, format!("ssh:{}", FINGERPRINT));
+
+        let info = IdentityInfo {
+            service: Service::new("ssh", FINGERPRINT),
+            claims: vec![],
+            status: IdentityStatus::Active(url),
+            key: Some(KEY.to_string()),
+        };
+        assert_eq!(info.ssh_key(), Some(KEY.trim()));
+    }
+
+    #[test]
+    fn test_reject_invalid_proof() {
+        let other = "SHA256:WvUR6hRuXhDIM3PjMYnQ1BvPHz9vf5WKqS1qS3Fs0kg";
+        assert!(SshService
+            .verify_attached(other, SIGNATURE, KEY, PROOF)
+            .is_err());
+        assert!(SshService
+            .verify_attached(FINGERPRINT, "mb3RoZXI", KEY, PROOF)
+            .is_err());
+        assert!(SshService
+            .verify_attached(FINGERPRINT, SIGNATURE, KEY, NAMESPACE_PROOF)
+            .is_err());
+        assert!(SshService
+            .verify_attached(FINGERPRINT, SIGNATURE, "not a key", PROOF)
+            .is_err());
+    }
+}
@@ -1,15 +1,19 @@
 //! Subxt calls.
 use codec::{Decode, Encode, FullCodec};
 use core::fmt::Display;
+use core::marker::PhantomData;
 use frame_support::Parameter;
 use libipld::cid::Cid;
 use std::str::FromStr;
 use substrate_subxt::sp_runtime::traits::{CheckedAdd, Member};
 use substrate_subxt::system::{System, SystemEventsDecoder};
 use substrate_subxt::{module, Call, Event, Store};
+use sunshine_identity_utils::Role;
 
 #[module]
 pub trait Identity: System {
+    #![event_type(Role)]
+
     type Uid: Parameter + Member + Copy + Default + CheckedAdd + Into<u64> + FromStr + Display;
 
     type Cid: Parameter + Member + Default + From<Cid> + Into<Cid>;
@@ -33,6 +37,13 @@ pub struct KeysStore<T: Identity> {
     uid: T::Uid,
 }
 
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct EncryptionKeysStore<'a, T: Identity> {
+    #[store(returns = Option<[u8; 32]>)]
+    uid: T::Uid,
+    key: &'a <T as System>::AccountId,
+}
+
 #[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
 pub struct IdentityStore<T: Identity> {
     #[store(returns = Option<T::Cid>)]
@@ -58,6 +69,61 @@ pub struct AccountStore<T: Identity> {
     uid: T::Uid,
 }
 
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct TombstoneStore<T: Identity> {
+    #[store(returns = bool)]
+    uid: T::Uid,
+}
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct RedirectStore<T: Identity> {
+    #[store(returns = Option<T::Uid>)]
+    uid: T::Uid,
+}
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct UsernameStore<T: Identity> {
+    #[store(returns = Option<Vec<u8>>)]
+    uid: T::Uid,
+}
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct UsernameLookupStore<'a, T: Identity> {
+    #[store(returns = Option<T::Uid>)]
+    username: &'a [u8],
+}
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct MembersStore<T: Identity> {
+    #[store(returns = Vec<(T::Uid, Role)>)]
+    team: T::Uid,
+}
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct TeamsStore<T: Identity> {
+    #[store(returns = Vec<T::Uid>)]
+    uid: T::Uid,
+}
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct TeamKeyGenStore<T: Identity> {
+    #[store(returns = T::Gen)]
+    team: T::Uid,
+}
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct TeamKeysStore<T: Identity> {
+    #[store(returns = Option<T::Cid>)]
+    team: T::Uid,
+    gen: T::Gen,
+}
+
+#[derive(Clone, Debug, Eq, Encode, PartialEq, Store)]
+pub struct TeamKeyRevokedStore<T: Identity> {
+    #[store(returns = bool)]
+    team: T::Uid,
+}
+
 #[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
 pub struct CreateAccountForCall<'a, T: Identity> {
     key: &'a <T as System>::AccountId,
@@ -73,6 +139,12 @@ pub struct RemoveKeyCall<'a, T: Identity> {
     key: &'a <T as System>::AccountId,
 }
 
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct SetEncryptionKeyCall<'a, T: Identity> {
+    encryption_key: &'a [u8; 32],
+    pub _runtime: PhantomData<T>,
+}
+
 #[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
 pub struct ChangePasswordCall<'a, T: Identity> {
     password_mask: &'a T::Mask,
@@ -85,32 +157,154 @@ pub struct SetIdentityCall<'a, T: Identity> {
     new_cid: &'a T::Cid,
 }
 
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct CloseAccountCall<'a, T: Identity> {
+    beneficiary: &'a <T as System>::AccountId,
+}
+
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct MergeAccountCall<T: Identity> {
+    from: T::Uid,
+    into: T::Uid,
+    keys: u32,
+    teams: u32,
+}
+
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct SetUsernameCall<'a, T: Identity> {
+    username: &'a [u8],
+}
+
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct ClearUsernameCall<T: Identity> {
+    pub _runtime: PhantomData<T>,
+}
+
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct CreateTeamCall<T: Identity> {
+    pub _runtime: PhantomData<T>,
+}
+
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct AddMemberCall<T: Identity> {
+    team: T::Uid,
+    member: T::Uid,
+    role: Role,
+}
+
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct RemoveMemberCall<T: Identity> {
+    team: T::Uid,
+    member: T::Uid,
+}
+
+#[derive(Call, Clone, Debug, Eq, Encode, PartialEq)]
+pub struct SetTeamKeyCall<'a, T: Identity> {
+    team: T::Uid,
+    gen: T::Gen,
+    cid: &'a T::Cid,
+}
+
 #[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
 pub struct AccountCreatedEvent<T: Identity> {
-    uid: T::Uid,
+    pub uid: T::Uid,
 }
 
 #[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
 pub struct KeyAddedEvent<T: Identity> {
-    uid: T::Uid,
-    key: <T as System>::AccountId,
+    pub uid: T::Uid,
+    pub key: <T as System>::AccountId,
 }
 
 #[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
 pub struct KeyRemovedEvent<T: Identity> {
+    pub uid: T::Uid,
+    pub key: <T as System>::AccountId,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct EncryptionKeySetEvent<T: Identity> {
     uid: T::Uid,
     key: <T as System>::AccountId,
+    encryption_key: [u8; 32],
 }
 
 #[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
 pub struct IdentityChangedEvent<T: Identity> {
-    uid: T::Uid,
-    cid: T::Cid,
+    pub uid: T::Uid,
+    pub cid: T::Cid,
 }
 
 #[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
 pub struct PasswordChangedEvent<T: Identity> {
+    pub uid: T::Uid,
+    pub gen: T::Gen,
+    pub mask: T::Mask,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct AccountCloseRequestedEvent<T: Identity> {
     uid: T::Uid,
-    gen: T::Gen,
-    mask: T::Mask,
+    key: <T as System>::AccountId,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct AccountClosedEvent<T: Identity> {
+    uid: T::Uid,
+    beneficiary: <T as System>::AccountId,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct AccountMergeRequestedEvent<T: Identity> {
+    from: T::Uid,
+    into: T::Uid,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct AccountMergedEvent<T: Identity> {
+    from: T::Uid,
+    into: T::Uid,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct UsernameSetEvent<T: Identity> {
+    uid: T::Uid,
+    username: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct UsernameClearedEvent<T: Identity> {
+    uid: T::Uid,
+    username: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct TeamCreatedEvent<T: Identity> {
+    pub team: T::Uid,
+    pub owner: T::Uid,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct MemberAddedEvent<T: Identity> {
+    pub team: T::Uid,
+    pub member: T::Uid,
+    pub role: Role,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct MemberRemovedEvent<T: Identity> {
+    pub team: T::Uid,
+    pub member: T::Uid,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct TeamKeyChangedEvent<T: Identity> {
+    pub team: T::Uid,
+    pub gen: T::Gen,
+    pub cid: T::Cid,
+}
+
+#[derive(Clone, Debug, Decode, Eq, Event, PartialEq)]
+pub struct TeamKeyRevokedEvent<T: Identity> {
+    pub team: T::Uid,
 }
@@ -0,0 +1,88 @@
+use crate::error::{DecryptionFailed, EncryptionFailed, InvalidKey};
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::ChaCha20Poly1305;
+use core::convert::TryInto;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sunshine_client_utils::Result;
+
+/// A generation of the symmetric key shared by the members of a team.
+///
+/// A new generation is published when a member joins and whenever a
+/// member or a device of a member is removed, so removed devices can't
+/// read data encrypted with later generations.
+#[derive(Clone)]
+pub struct TeamKey {
+    pub team: u64,
+    pub gen: u16,
+    key: [u8; 32],
+}
+
+impl TeamKey {
+    /// Generates a random key.
+    pub(crate) fn generate(team: u64, gen: u16) -> Self {
+        let mut key = [0; 32];
+        OsRng.fill_bytes(&mut key);
+        Self { team, gen, key }
+    }
+
+    pub(crate) fn from_bytes(team: u64, gen: u16, key: &[u8]) -> Result<Self> {
+        let key = key.try_into().map_err(|_| InvalidKey)?;
+        Ok(Self { team, gen, key })
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Encrypts data with the key. The random nonce is prepended to the
+    /// ciphertext.
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0; 12];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = ChaCha20Poly1305::new(GenericArray::from_slice(&self.key))
+            .encrypt(GenericArray::from_slice(&nonce), data)
+            .map_err(|_| EncryptionFailed)?;
+        let mut bytes = nonce.to_vec();
+        bytes.extend(ciphertext);
+        Ok(bytes)
+    }
+
+    /// Decrypts data encrypted with `encrypt`.
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < 12 {
+            return Err(DecryptionFailed.into());
+        }
+        let (nonce, ciphertext) = data.split_at(12);
+        let data = ChaCha20Poly1305::new(GenericArray::from_slice(&self.key))
+            .decrypt(GenericArray::from_slice(nonce), ciphertext)
+            .map_err(|_| DecryptionFailed)?;
+        Ok(data)
+    }
+}
+
+impl core::fmt::Debug for TeamKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("TeamKey")
+            .field("team", &self.team)
+            .field("gen", &self.gen)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_team_key() {
+        let key = TeamKey::generate(1, 1);
+        let data = key.encrypt(b"hello").unwrap();
+        assert_eq!(key.decrypt(&data).unwrap(), b"hello");
+        let key2 = TeamKey::from_bytes(1, 2, key.as_bytes()).unwrap();
+        assert_eq!(key2.decrypt(&data).unwrap(), b"hello");
+        assert!(TeamKey::generate(1, 3).decrypt(&data).is_err());
+        assert!(TeamKey::from_bytes(1, 2, &[0; 16]).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::claim::{IdentityInfo, IdentityStatus};
+use crate::service::Service;
+use libipld::DagCbor;
+
+/// A verified proof pinned by a track claim.
+#[derive(Clone, Debug, Eq, PartialEq, DagCbor)]
+pub struct TrackedProof {
+    pub service: Service,
+    /// The url of the proof.
+    pub proof: String,
+    /// The key that signed an attached proof.
+    pub key: Option<String>,
+}
+
+/// The verified proofs of a user at the time they were tracked.
+#[derive(Clone, Debug, Default, Eq, PartialEq, DagCbor)]
+pub struct TrackSnapshot {
+    pub uid: u64,
+    pub proofs: Vec<TrackedProof>,
+}
+
+impl TrackSnapshot {
+    /// Creates a snapshot of the active proofs of a user.
+    pub fn new(uid: u64, ids: &[IdentityInfo]) -> Self {
+        let mut proofs: Vec<_> = ids
+            .iter()
+            .filter_map(|id| match &id.status {
+                IdentityStatus::Active(proof) => Some(TrackedProof {
+                    service: id.service.clone(),
+                    proof: proof.clone(),
+                    key: id.key.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+        proofs.sort_by(|a, b| a.service.to_string().cmp(&b.service.to_string()));
+        Self { uid, proofs }
+    }
+
+    /// Returns the tracked services whose proof is no longer active or was
+    /// replaced by a different proof or key.
+    pub fn changed(&self, ids: &[IdentityInfo]) -> Vec<Service> {
+        let current = Self::new(self.uid, ids);
+        self.proofs
+            .iter()
+            .filter(|proof| !current.proofs.contains(proof))
+            .map(|proof| proof.service.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(service: &Service, status: IdentityStatus) -> IdentityInfo {
+        IdentityInfo {
+            service: service.clone(),
+            claims: vec![],
+            status,
+            key: None,
+        }
+    }
+
+    #[test]
+    fn test_track_snapshot() {
+        let github = Service::new("github", "dvc94ch");
+        let dns = Service::new("dns", "example.com");
+        let gist = IdentityStatus::Active("https://gist".into());
+        let snapshot = TrackSnapshot::new(
+            1,
+            &[
+                info(&github, gist.clone()),
+                info(&dns, IdentityStatus::ProofNotFound),
+            ],
+        );
+        assert_eq!(snapshot.proofs.len(), 1);
+
+        let ids = [info(&github, gist), info(&dns, IdentityStatus::Revoked)];
+        assert!(snapshot.changed(&ids).is_empty());
+
+        let moved = IdentityStatus::Active("https://gist2".into());
+        assert_eq!(
+            snapshot.changed(&[info(&github, moved)]),
+            vec![github.clone()]
+        );
+        assert_eq!(snapshot.changed(&[]), vec![github]);
+    }
+}
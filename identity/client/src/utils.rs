@@ -5,6 +5,7 @@ use sp_core::crypto::Ss58Codec;
 use std::str::FromStr;
 use substrate_subxt::{sp_core, system::System};
 use sunshine_client_utils::{crypto::ss58::Ss58, Node, Result};
+use sunshine_identity_utils::normalize_username;
 
 pub async fn resolve<N, C>(
     client: &C,
@@ -21,9 +22,10 @@ where
         Identifier::Account(client.signer()?.account_id().clone())
     };
     let uid = match identifier {
-        Identifier::Uid(uid) => uid,
+        Identifier::Uid(uid) => client.follow_redirect(uid).await?,
         Identifier::Account(account_id) => client.fetch_uid(&account_id).await?.ok_or(NoAccount)?,
         Identifier::Service(service) => client.resolve(&service).await?,
+        Identifier::Username(username) => client.resolve_username(&username).await?,
     };
     Ok(uid)
 }
@@ -33,6 +35,7 @@ pub enum Identifier<R: Identity> {
     Uid(R::Uid),
     Account(R::AccountId),
     Service(Service),
+    Username(String),
 }
 
 impl<R: Identity> core::fmt::Display for Identifier<R> {
@@ -41,6 +44,7 @@ impl<R: Identity> core::fmt::Display for Identifier<R> {
             Self::Uid(uid) => write!(f, "{}", uid),
             Self::Account(account_id) => write!(f, "{}", account_id.to_string()),
             Self::Service(service) => write!(f, "{}", service),
+            Self::Username(username) => write!(f, "@{}", username),
         }
     }
 }
@@ -52,7 +56,13 @@ where
     type Err = ServiceParseError;
 
     fn from_str(string: &str) -> core::result::Result<Self, Self::Err> {
-        if let Ok(uid) = R::Uid::from_str(string) {
+        if let Some(username) = string.strip_prefix('@') {
+            let username =
+                normalize_username(username.as_bytes()).ok_or(ServiceParseError::Invalid)?;
+            Ok(Self::Username(
+                String::from_utf8(username).expect("usernames are ascii; qed"),
+            ))
+        } else if let Ok(uid) = R::Uid::from_str(string) {
             Ok(Self::Uid(uid))
         } else if let Ok(Ss58(account_id)) = Ss58::<R>::from_str(string) {
             Ok(Self::Account(account_id))
@@ -73,16 +83,35 @@ mod tests {
     fn parse_identifer() {
         assert_eq!(
             Identifier::from_str("dvc94ch@github"),
-            Ok(Identifier::<Runtime>::Service(Service::Github(
-                "dvc94ch".into()
+            Ok(Identifier::<Runtime>::Service(Service::new(
+                "github", "dvc94ch"
             )))
         );
+        assert_eq!(
+            Identifier::from_str("example.com@dns"),
+            Ok(Identifier::<Runtime>::Service(Service::new(
+                "dns",
+                "example.com"
+            )))
+        );
+        assert_eq!(
+            Identifier::<Runtime>::from_str("localhost@dns"),
+            Err(ServiceParseError::Invalid)
+        );
         assert_eq!(
             Identifier::<Runtime>::from_str("dvc94ch@twitter"),
             Err(ServiceParseError::Unknown("twitter".into()))
         );
         assert_eq!(
             Identifier::<Runtime>::from_str("@dvc94ch"),
+            Ok(Identifier::Username("dvc94ch".into()))
+        );
+        assert_eq!(
+            Identifier::<Runtime>::from_str("@Alice"),
+            Ok(Identifier::Username("alice".into()))
+        );
+        assert_eq!(
+            Identifier::<Runtime>::from_str("@"),
             Err(ServiceParseError::Invalid)
         );
         let alice = AccountKeyring::Alice.to_account_id();
@@ -213,7 +213,7 @@ where
 
     pub async fn prove(&self, service: &str) -> Result<Vec<String>> {
         let service: Service = service.parse()?;
-        let instructions = service.cli_instructions();
+        let instructions = service.cli_instructions()?;
         let proof = self.client.read().await.prove_identity(service).await?;
         Ok(vec![instructions, proof])
     }
@@ -0,0 +1,255 @@
+//! Benchmarks for the identity module.
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+
+const SEED: u32 = 0;
+
+fn funded_key<T: Trait>(name: &'static str, index: u32) -> <T as System>::AccountId {
+    let key: <T as System>::AccountId = account(name, index, SEED);
+    // Funding the key implicitly creates an account for it.
+    T::Currency::make_free_balance_be(&key, BalanceOf::<T>::max_value() / 2u32.into());
+    key
+}
+
+fn create_teams<T: Trait>(owner: &<T as System>::AccountId, t: u32) -> DispatchResult {
+    for _ in 0..t {
+        Module::<T>::create_team(RawOrigin::Signed(owner.clone()).into())?;
+    }
+    Ok(())
+}
+
+fn add_members<T: Trait>(owner: &<T as System>::AccountId, team: T::Uid, m: u32) -> DispatchResult {
+    for i in 1..m {
+        let key = funded_key::<T>("member", i);
+        let uid = <UidLookup<T>>::get(&key).unwrap();
+        Module::<T>::add_member(
+            RawOrigin::Signed(owner.clone()).into(),
+            team,
+            uid,
+            Role::Reader,
+        )?;
+    }
+    Ok(())
+}
+
+fn last_team<T: Trait>(owner: &<T as System>::AccountId) -> T::Uid {
+    let uid = <UidLookup<T>>::get(owner).unwrap();
+    *<Teams<T>>::get(uid).last().unwrap()
+}
+
+benchmarks! {
+    _ { }
+
+    create_account_for {
+        let caller = funded_key::<T>("caller", 0);
+        let key: <T as System>::AccountId = account("key", 0, SEED);
+    }: _(RawOrigin::Signed(caller), key.clone())
+    verify {
+        assert!(<UidLookup<T>>::get(&key).is_some());
+    }
+
+    add_key {
+        let caller = funded_key::<T>("caller", 0);
+        let key: <T as System>::AccountId = account("key", 0, SEED);
+    }: _(RawOrigin::Signed(caller.clone()), key.clone())
+    verify {
+        assert_eq!(<UidLookup<T>>::get(&key), <UidLookup<T>>::get(&caller));
+    }
+
+    remove_key {
+        let t in 0 .. T::MaxTeams::get();
+        let caller = funded_key::<T>("caller", 0);
+        let key: <T as System>::AccountId = account("key", 0, SEED);
+        create_teams::<T>(&caller, t)?;
+        Module::<T>::add_key(RawOrigin::Signed(caller.clone()).into(), key.clone())?;
+    }: _(RawOrigin::Signed(caller.clone()), key.clone())
+    verify {
+        let uid = <UidLookup<T>>::get(&caller).unwrap();
+        assert!(!<Keys<T>>::get(uid).contains(&key));
+    }
+
+    set_encryption_key {
+        let caller = funded_key::<T>("caller", 0);
+    }: _(RawOrigin::Signed(caller.clone()), [1; 32])
+    verify {
+        let uid = <UidLookup<T>>::get(&caller).unwrap();
+        assert_eq!(<EncryptionKeys<T>>::get(uid, &caller), Some([1; 32]));
+    }
+
+    change_password {
+        let caller = funded_key::<T>("caller", 0);
+    }: _(RawOrigin::Signed(caller.clone()), T::Mask::default(), 1u8.into())
+    verify {
+        let uid = <UidLookup<T>>::get(&caller).unwrap();
+        assert_eq!(<PasswordGen<T>>::get(uid), 1u8.into());
+    }
+
+    set_identity {
+        let caller = funded_key::<T>("caller", 0);
+    }: _(RawOrigin::Signed(caller.clone()), None, T::Cid::default())
+    verify {
+        let uid = <UidLookup<T>>::get(&caller).unwrap();
+        assert!(<Identity<T>>::get(uid).is_some());
+    }
+
+    close_account {
+        let k in 2 .. T::MaxKeys::get();
+        let t in 0 .. T::MaxTeams::get();
+        let caller = funded_key::<T>("caller", 0);
+        let key: <T as System>::AccountId = account("key", 0, SEED);
+        let beneficiary: <T as System>::AccountId = account("beneficiary", 0, SEED);
+        create_teams::<T>(&caller, t)?;
+        Module::<T>::add_key(RawOrigin::Signed(caller.clone()).into(), key.clone())?;
+        for i in 2..k {
+            let key: <T as System>::AccountId = account("key", i, SEED);
+            Module::<T>::add_key(RawOrigin::Signed(caller.clone()).into(), key.clone())?;
+            Module::<T>::set_encryption_key(RawOrigin::Signed(key).into(), [1; 32])?;
+        }
+        Module::<T>::set_username(RawOrigin::Signed(caller.clone()).into(), b"alice".to_vec())?;
+        Module::<T>::close_account(RawOrigin::Signed(key).into(), beneficiary.clone())?;
+    }: _(RawOrigin::Signed(caller.clone()), beneficiary)
+    verify {
+        let uid = <UidLookup<T>>::get(&caller).unwrap();
+        assert!(<Tombstone<T>>::get(uid));
+    }
+
+    set_username {
+        let caller = funded_key::<T>("caller", 0);
+    }: _(RawOrigin::Signed(caller.clone()), b"alice".to_vec())
+    verify {
+        let uid = <UidLookup<T>>::get(&caller).unwrap();
+        assert!(<Username<T>>::get(uid).is_some());
+    }
+
+    clear_username {
+        let caller = funded_key::<T>("caller", 0);
+        Module::<T>::set_username(RawOrigin::Signed(caller.clone()).into(), b"alice".to_vec())?;
+    }: _(RawOrigin::Signed(caller.clone()))
+    verify {
+        let uid = <UidLookup<T>>::get(&caller).unwrap();
+        assert!(<Username<T>>::get(uid).is_none());
+    }
+
+    merge_account {
+        let k in 1 .. T::MaxKeys::get() - 1;
+        let t in 0 .. T::MaxTeams::get();
+        let caller = funded_key::<T>("caller", 0);
+        let other = funded_key::<T>("other", 0);
+        create_teams::<T>(&other, t)?;
+        for i in 1..k {
+            let key: <T as System>::AccountId = account("key", i, SEED);
+            Module::<T>::add_key(RawOrigin::Signed(other.clone()).into(), key)?;
+        }
+        let from = <UidLookup<T>>::get(&other).unwrap();
+        let into = <UidLookup<T>>::get(&caller).unwrap();
+        Module::<T>::merge_account(RawOrigin::Signed(other).into(), from, into, k, t)?;
+    }: _(RawOrigin::Signed(caller), from, into, k, t)
+    verify {
+        assert_eq!(<Redirect<T>>::get(from), Some(into));
+        assert_eq!(<Teams<T>>::get(into).len() as u32, t);
+    }
+
+    create_team {
+        let caller = funded_key::<T>("caller", 0);
+    }: _(RawOrigin::Signed(caller.clone()))
+    verify {
+        let uid = <UidLookup<T>>::get(&caller).unwrap();
+        let team = last_team::<T>(&caller);
+        assert_eq!(Module::<T>::role(team, uid), Some(Role::Owner));
+    }
+
+    add_member {
+        let m in 1 .. T::MaxMembers::get() - 1;
+        let caller = funded_key::<T>("caller", 0);
+        let member = funded_key::<T>("member", 0);
+        Module::<T>::create_team(RawOrigin::Signed(caller.clone()).into())?;
+        let team = last_team::<T>(&caller);
+        add_members::<T>(&caller, team, m)?;
+        let uid = <UidLookup<T>>::get(&member).unwrap();
+    }: _(RawOrigin::Signed(caller), team, uid, Role::Writer)
+    verify {
+        assert_eq!(Module::<T>::role(team, uid), Some(Role::Writer));
+    }
+
+    remove_member {
+        let m in 2 .. T::MaxMembers::get();
+        let caller = funded_key::<T>("caller", 0);
+        let member = funded_key::<T>("member", 0);
+        Module::<T>::create_team(RawOrigin::Signed(caller.clone()).into())?;
+        let team = last_team::<T>(&caller);
+        add_members::<T>(&caller, team, m - 1)?;
+        let uid = <UidLookup<T>>::get(&member).unwrap();
+        Module::<T>::add_member(RawOrigin::Signed(caller.clone()).into(), team, uid, Role::Owner)?;
+    }: _(RawOrigin::Signed(caller), team, uid)
+    verify {
+        assert_eq!(Module::<T>::role(team, uid), None);
+        assert!(<TeamKeyRevoked<T>>::get(team));
+    }
+
+    set_team_key {
+        let caller = funded_key::<T>("caller", 0);
+        Module::<T>::create_team(RawOrigin::Signed(caller.clone()).into())?;
+        let team = last_team::<T>(&caller);
+    }: _(RawOrigin::Signed(caller), team, 1u8.into(), T::Cid::default())
+    verify {
+        assert_eq!(<TeamKeyGen<T>>::get(team), 1u8.into());
+        assert!(<TeamKeys<T>>::get(team, T::Gen::from(1u8)).is_some());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_account_for::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_add_key::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_remove_key::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_encryption_key::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_change_password::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_identity::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_close_account::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_username::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_clear_username::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_merge_account::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_team::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_add_member::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_remove_member::<Test>());
+        });
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_team_key::<Test>());
+        });
+    }
+}
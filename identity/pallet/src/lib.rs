@@ -3,11 +3,15 @@
 
 use codec::FullCodec;
 use frame_support::dispatch::DispatchResult;
-use frame_support::traits::StoredMap;
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure, Parameter};
+use frame_support::traits::{Currency, ExistenceRequirement, Get, ReservableCurrency, StoredMap};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure, transactional, Parameter,
+};
 use frame_system::{ensure_signed, Trait as System};
 use orml_utilities::OrderedSet;
-use sp_runtime::traits::{CheckedAdd, Member};
+use sp_runtime::traits::{CheckedAdd, CheckedSub, Member, Zero};
+use sp_std::prelude::*;
+use sunshine_identity_utils::normalize_username;
 
 #[cfg(test)]
 mod mock;
@@ -15,27 +19,61 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+mod benchmarking;
+pub mod weights;
+
+pub use sunshine_identity_utils::Role;
+pub use weights::WeightInfo;
+
 /// The pallet's configuration trait.
 pub trait Trait: System {
     /// User ID type.
     type Uid: Parameter + Member + Copy + Default + CheckedAdd + From<u8>;
 
     /// Cid type.
-    type Cid: Parameter + Member;
+    type Cid: Parameter + Member + Default;
 
     /// Mask type.
-    type Mask: Parameter + Member;
+    type Mask: Parameter + Member + Default;
 
     /// Generation type.
-    type Gen: Parameter + Member + Copy + Default + CheckedAdd + From<u8> + Ord;
+    type Gen: Parameter + Member + Copy + Default + CheckedAdd + CheckedSub + From<u8> + Ord;
+
+    /// Number of password masks kept per account.
+    type MaxPasswordMasks: Get<Self::Gen>;
 
     /// Data to be associated with an account.
     type AccountData: Member + FullCodec + Clone + Default;
 
+    /// Currency used for deposits and for draining closed accounts.
+    type Currency: ReservableCurrency<<Self as System>::AccountId>;
+
+    /// Deposit reserved for creating an account, returned when the
+    /// account is closed.
+    type AccountDeposit: Get<BalanceOf<Self>>;
+
+    /// Deposit reserved for registering a username.
+    type UsernameDeposit: Get<BalanceOf<Self>>;
+
+    /// Maximum number of keys of an account.
+    type MaxKeys: Get<u32>;
+
+    /// Maximum number of teams a uid can be a member of.
+    type MaxTeams: Get<u32>;
+
+    /// Maximum number of members of a team.
+    type MaxMembers: Get<u32>;
+
+    /// Weight information for the extrinsics.
+    type WeightInfo: WeightInfo;
+
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as System>::Event>;
 }
 
+type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as System>::AccountId>>::Balance;
+
 decl_storage! {
     trait Store for Module<T: Trait> as IdentityModule {
         UidCounter: T::Uid;
@@ -48,6 +86,11 @@ decl_storage! {
             hasher(blake2_128_concat) T::Uid
             => OrderedSet<<T as System>::AccountId>;
 
+        pub EncryptionKeys get(fn encryption_key): double_map
+            hasher(blake2_128_concat) T::Uid,
+            hasher(blake2_128_concat) <T as System>::AccountId
+            => Option<[u8; 32]>;
+
         pub Identity get(fn identity): map
             hasher(blake2_128_concat) T::Uid
             => Option<T::Cid>;
@@ -64,6 +107,56 @@ decl_storage! {
         pub Account get(fn account): map
             hasher(blake2_128_concat) T::Uid
             => <T as Trait>::AccountData;
+
+        pub AccountDeposits get(fn account_deposit): map
+            hasher(blake2_128_concat) T::Uid
+            => Option<(<T as System>::AccountId, BalanceOf<T>)>;
+
+        pub PendingClose get(fn pending_close): map
+            hasher(blake2_128_concat) T::Uid
+            => Option<(<T as System>::AccountId, <T as System>::AccountId)>;
+
+        pub Tombstone get(fn tombstone): map
+            hasher(blake2_128_concat) T::Uid
+            => bool;
+
+        pub PendingMerge get(fn pending_merge): double_map
+            hasher(blake2_128_concat) T::Uid,
+            hasher(blake2_128_concat) T::Uid
+            => Option<T::Uid>;
+
+        pub Redirect get(fn redirect): map
+            hasher(blake2_128_concat) T::Uid
+            => Option<T::Uid>;
+
+        pub Members get(fn members): map
+            hasher(blake2_128_concat) T::Uid
+            => Vec<(T::Uid, Role)>;
+
+        pub Teams get(fn teams): map
+            hasher(blake2_128_concat) T::Uid
+            => Vec<T::Uid>;
+
+        pub TeamKeyGen get(fn team_key_gen): map
+            hasher(blake2_128_concat) T::Uid
+            => T::Gen;
+
+        pub TeamKeys get(fn team_key): double_map
+            hasher(blake2_128_concat) T::Uid,
+            hasher(blake2_128_concat) T::Gen
+            => Option<T::Cid>;
+
+        pub TeamKeyRevoked get(fn team_key_revoked): map
+            hasher(blake2_128_concat) T::Uid
+            => bool;
+
+        pub Username get(fn username): map
+            hasher(blake2_128_concat) T::Uid
+            => Option<Vec<u8>>;
+
+        pub UsernameLookup get(fn username_lookup): map
+            hasher(blake2_128_concat) Vec<u8>
+            => Option<T::Uid>;
     }
 }
 
@@ -79,8 +172,20 @@ decl_event!(
         AccountCreated(Uid),
         KeyAdded(Uid, AccountId),
         KeyRemoved(Uid, AccountId),
+        EncryptionKeySet(Uid, AccountId, [u8; 32]),
         IdentityChanged(Uid, Cid),
         PasswordChanged(Uid, Gen, Mask),
+        AccountCloseRequested(Uid, AccountId),
+        AccountClosed(Uid, AccountId),
+        AccountMergeRequested(Uid, Uid),
+        AccountMerged(Uid, Uid),
+        UsernameSet(Uid, Vec<u8>),
+        UsernameCleared(Uid, Vec<u8>),
+        TeamCreated(Uid, Uid),
+        MemberAdded(Uid, Uid, Role),
+        MemberRemoved(Uid, Uid),
+        TeamKeyChanged(Uid, Gen, Cid),
+        TeamKeyRevoked(Uid),
     }
 );
 
@@ -102,6 +207,40 @@ decl_error! {
         PasswordGenOverflow,
         /// Password gen missmatch.
         PasswordGenMissmatch,
+        /// Account is closed.
+        AccountClosed,
+        /// Cant close account to self.
+        CantCloseToSelf,
+        /// Cant merge account with self.
+        CantMergeSelf,
+        /// Account was merged.
+        AccountMerged,
+        /// Both accounts have an identity.
+        IdentityConflict,
+        /// Invalid username.
+        InvalidUsername,
+        /// Username in use.
+        UsernameInUse,
+        /// No username.
+        NoUsername,
+        /// Not a member of the team.
+        NotMember,
+        /// Team needs an owner.
+        LastOwner,
+        /// Team key gen overflow.
+        TeamKeyGenOverflow,
+        /// Team key gen missmatch.
+        TeamKeyGenMissmatch,
+        /// Member of too many teams.
+        TooManyTeams,
+        /// Too many keys.
+        TooManyKeys,
+        /// Too many members.
+        TooManyMembers,
+        /// Key or team count lower than stored.
+        InvalidWitness,
+        /// Balance reserved by another module.
+        ReservedBalance,
     }
 }
 
@@ -115,28 +254,36 @@ decl_module! {
         fn deposit_event() = default;
 
         /// Create account.
-        #[weight = 0]
+        ///
+        /// Reserves a deposit from the caller that is returned when the
+        /// account is closed.
+        #[weight = T::WeightInfo::create_account_for()]
+        #[transactional]
         pub fn create_account_for(origin, key: <T as System>::AccountId) -> DispatchResult {
-            let _ = ensure_signed(origin)?;
+            let who = ensure_signed(origin)?;
             Self::ensure_key_unused(&key)?;
 
-            Self::create_account(key)?;
+            let deposit = T::AccountDeposit::get();
+            T::Currency::reserve(&who, deposit)?;
+            let uid = Self::create_account(key)?;
+            <AccountDeposits<T>>::insert(uid, (who, deposit));
             Ok(())
         }
 
         /// Add a key.
-        #[weight = 0]
+        #[weight = T::WeightInfo::add_key()]
         pub fn add_key(origin, key: <T as System>::AccountId) -> DispatchResult {
             let who = ensure_signed(origin)?;
             let uid = Self::ensure_uid(&who)?;
             Self::ensure_key_unused(&key)?;
+            Self::ensure_can_add_keys(uid, 1)?;
 
             Self::add_key_to_uid(uid, key);
             Ok(())
         }
 
         /// Remove a key.
-        #[weight = 0]
+        #[weight = T::WeightInfo::remove_key(T::MaxTeams::get())]
         pub fn remove_key(origin, key: <T as System>::AccountId) -> DispatchResult {
             let who = ensure_signed(origin)?;
             let uid = Self::ensure_uid(&who)?;
@@ -148,8 +295,19 @@ decl_module! {
             Ok(())
         }
 
+        /// Set the X25519 encryption key of the calling key.
+        #[weight = T::WeightInfo::set_encryption_key()]
+        pub fn set_encryption_key(origin, encryption_key: [u8; 32]) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let uid = Self::ensure_uid(&who)?;
+
+            <EncryptionKeys<T>>::insert(uid, &who, encryption_key);
+            Self::deposit_event(RawEvent::EncryptionKeySet(uid, who, encryption_key));
+            Ok(())
+        }
+
         /// Change password.
-        #[weight = 0]
+        #[weight = T::WeightInfo::change_password()]
         pub fn change_password(origin, mask: T::Mask, gen: T::Gen) -> DispatchResult {
             let who = ensure_signed(origin)?;
             let uid = Self::ensure_uid(&who)?;
@@ -162,12 +320,16 @@ decl_module! {
 
             <PasswordGen<T>>::insert(uid, gen);
             <PasswordMask<T>>::insert(uid, gen, mask.clone());
+            // Devices that are further behind need to be provisioned again.
+            if let Some(old_gen) = gen.checked_sub(&T::MaxPasswordMasks::get()) {
+                <PasswordMask<T>>::remove(uid, old_gen);
+            }
             Self::deposit_event(RawEvent::PasswordChanged(uid, gen, mask));
             Ok(())
         }
 
         /// Set the identity.
-        #[weight = 0]
+        #[weight = T::WeightInfo::set_identity()]
         pub fn set_identity(origin, prev_cid: Option<T::Cid>, new_cid: T::Cid) -> DispatchResult {
             let who = ensure_signed(origin)?;
             let uid = Self::ensure_uid(&who)?;
@@ -177,12 +339,236 @@ decl_module! {
             Self::deposit_event(RawEvent::IdentityChanged(uid, new_cid));
             Ok(())
         }
+
+        /// Close account.
+        ///
+        /// Needs to be called by two different keys of the account with
+        /// the same beneficiary. The first call records the request, the
+        /// second call transfers the free balance to the beneficiary and
+        /// closes the account.
+        ///
+        /// The keys of a closed account keep pointing to its tombstone, so
+        /// funds sent to them after the account was closed are locked.
+        #[weight = T::WeightInfo::close_account(T::MaxKeys::get(), T::MaxTeams::get())]
+        #[transactional]
+        pub fn close_account(origin, beneficiary: <T as System>::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let uid = Self::ensure_uid(&who)?;
+            ensure!(Self::lookup(&beneficiary) != Some(uid), Error::<T>::CantCloseToSelf);
+
+            match <PendingClose<T>>::get(uid) {
+                Some((key, pending_beneficiary))
+                    if key != who
+                        && pending_beneficiary == beneficiary
+                        && <Keys<T>>::get(uid).contains(&key) =>
+                {
+                    Self::close_uid(uid, who, beneficiary)?;
+                }
+                _ => {
+                    <PendingClose<T>>::insert(uid, (who.clone(), beneficiary));
+                    Self::deposit_event(RawEvent::AccountCloseRequested(uid, who));
+                }
+            }
+            Ok(())
+        }
+
+        /// Set the username.
+        ///
+        /// Reserves a deposit when the account doesn't have a username yet.
+        #[weight = T::WeightInfo::set_username()]
+        pub fn set_username(origin, username: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let uid = Self::ensure_uid(&who)?;
+            let username = normalize_username(&username).ok_or(Error::<T>::InvalidUsername)?;
+            ensure!(<UsernameLookup<T>>::get(&username).is_none(), Error::<T>::UsernameInUse);
+
+            if let Some(prev) = <Username<T>>::get(uid) {
+                <UsernameLookup<T>>::remove(prev);
+            } else {
+                T::Currency::reserve(&who, T::UsernameDeposit::get())?;
+            }
+            <UsernameLookup<T>>::insert(username.clone(), uid);
+            <Username<T>>::insert(uid, username.clone());
+            Self::deposit_event(RawEvent::UsernameSet(uid, username));
+            Ok(())
+        }
+
+        /// Clear the username and return the deposit.
+        #[weight = T::WeightInfo::clear_username()]
+        pub fn clear_username(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let uid = Self::ensure_uid(&who)?;
+            ensure!(<Username<T>>::get(uid).is_some(), Error::<T>::NoUsername);
+
+            Self::release_username(uid, &who);
+            Ok(())
+        }
+
+        /// Create a team owned by the caller.
+        ///
+        /// A team is a uid without keys whose members are other uids.
+        #[weight = T::WeightInfo::create_team()]
+        pub fn create_team(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let owner = Self::ensure_uid(&who)?;
+            Self::ensure_can_join_teams(owner, 1)?;
+
+            let team = Self::next_uid()?;
+            <Members<T>>::insert(team, sp_std::vec![(owner, Role::Owner)]);
+            <Teams<T>>::mutate(owner, |teams| teams.push(team));
+            Self::deposit_event(RawEvent::TeamCreated(team, owner));
+            Self::deposit_event(RawEvent::MemberAdded(team, owner, Role::Owner));
+            Ok(())
+        }
+
+        /// Add a member to a team or change the role of a member.
+        ///
+        /// Needs to be called by an admin of the team. Admins can't grant
+        /// or change roles above their own.
+        #[weight = T::WeightInfo::add_member(T::MaxMembers::get())]
+        pub fn add_member(origin, team: T::Uid, member: T::Uid, role: Role) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let uid = Self::ensure_uid(&who)?;
+            let caller = Self::ensure_role(team, uid, Role::Admin)?;
+            ensure!(!<Keys<T>>::get(member).0.is_empty(), Error::<T>::NoAccount);
+            let prev = Self::role(team, member);
+            ensure!(role <= caller && prev.unwrap_or(role) <= caller, Error::<T>::Unauthorized);
+            if prev == Some(Role::Owner) && role != Role::Owner {
+                Self::ensure_owner_remains(team)?;
+            }
+            if prev.is_none() {
+                Self::ensure_can_join_teams(member, 1)?;
+                Self::ensure_can_add_members(team, 1)?;
+            }
+
+            <Members<T>>::mutate(team, |members| {
+                members.retain(|(uid, _)| *uid != member);
+                members.push((member, role));
+            });
+            if prev.is_none() {
+                <Teams<T>>::mutate(member, |teams| teams.push(team));
+            }
+            Self::deposit_event(RawEvent::MemberAdded(team, member, role));
+            Ok(())
+        }
+
+        /// Remove a member from a team.
+        ///
+        /// Needs to be called by an admin of the team or by the member
+        /// leaving the team.
+        #[weight = T::WeightInfo::remove_member(T::MaxMembers::get())]
+        pub fn remove_member(origin, team: T::Uid, member: T::Uid) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let uid = Self::ensure_uid(&who)?;
+            let role = Self::role(team, member).ok_or(Error::<T>::NotMember)?;
+            if uid != member {
+                let caller = Self::ensure_role(team, uid, Role::Admin)?;
+                ensure!(role <= caller, Error::<T>::Unauthorized);
+            }
+            if role == Role::Owner {
+                Self::ensure_owner_remains(team)?;
+            }
+
+            <Members<T>>::mutate(team, |members| members.retain(|(uid, _)| *uid != member));
+            <Teams<T>>::mutate(member, |teams| teams.retain(|uid| *uid != team));
+            Self::deposit_event(RawEvent::MemberRemoved(team, member));
+            Self::revoke_team_key(team);
+            Ok(())
+        }
+
+        /// Publish the next generation of the team key.
+        ///
+        /// The cid points to the key encrypted to the devices of all
+        /// members. Needs to be called by a writer of the team.
+        #[weight = T::WeightInfo::set_team_key()]
+        pub fn set_team_key(origin, team: T::Uid, gen: T::Gen, cid: T::Cid) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let uid = Self::ensure_uid(&who)?;
+            Self::ensure_role(team, uid, Role::Writer)?;
+            ensure!(
+                gen == <TeamKeyGen<T>>::get(team)
+                    .checked_add(&1u8.into())
+                    .ok_or(Error::<T>::TeamKeyGenOverflow)?,
+                Error::<T>::TeamKeyGenMissmatch
+            );
+
+            <TeamKeyGen<T>>::insert(team, gen);
+            <TeamKeys<T>>::insert(team, gen, cid.clone());
+            <TeamKeyRevoked<T>>::remove(team);
+            Self::deposit_event(RawEvent::TeamKeyChanged(team, gen, cid));
+            Ok(())
+        }
+
+        /// Merge account `from` into account `into`.
+        ///
+        /// Needs to be called by a key of each account. Moves the keys,
+        /// the balance, the team memberships and the identity of `from`
+        /// into `into` and leaves a redirect. Claim chains can't be
+        /// combined, so the merge is refused if both accounts have an
+        /// identity. `keys` and `teams` are upper bounds of the number of
+        /// keys and teams of `from`.
+        #[weight = T::WeightInfo::merge_account(*keys, *teams)]
+        #[transactional]
+        pub fn merge_account(
+            origin,
+            from: T::Uid,
+            into: T::Uid,
+            keys: u32,
+            teams: u32,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let uid = Self::ensure_uid(&who)?;
+            ensure!(from != into, Error::<T>::CantMergeSelf);
+            ensure!(uid == from || uid == into, Error::<T>::Unauthorized);
+            for id in &[from, into] {
+                ensure!(!<Tombstone<T>>::get(id), Error::<T>::AccountClosed);
+                ensure!(<Redirect<T>>::get(id).is_none(), Error::<T>::AccountMerged);
+            }
+            ensure!(
+                <Keys<T>>::get(from).0.len() as u32 <= keys
+                    && <Teams<T>>::get(from).len() as u32 <= teams,
+                Error::<T>::InvalidWitness
+            );
+            ensure!(
+                <Identity<T>>::get(from).is_none() || <Identity<T>>::get(into).is_none(),
+                Error::<T>::IdentityConflict
+            );
+            Self::ensure_can_add_keys(into, <Keys<T>>::get(from).0.len())?;
+            let into_teams = <Teams<T>>::get(into);
+            let new_teams = <Teams<T>>::get(from)
+                .into_iter()
+                .filter(|team| !into_teams.contains(team))
+                .count();
+            Self::ensure_can_join_teams(into, new_teams)?;
+
+            match <PendingMerge<T>>::get(from, into) {
+                Some(approved_by) if approved_by != uid => {
+                    Self::merge_uids(from, into)?;
+                }
+                _ => {
+                    <PendingMerge<T>>::insert(from, into, uid);
+                    Self::deposit_event(RawEvent::AccountMergeRequested(from, into));
+                }
+            }
+            Ok(())
+        }
     }
 }
 
 impl<T: Trait> Module<T> {
+    fn lookup(key: &<T as System>::AccountId) -> Option<T::Uid> {
+        let mut uid = <UidLookup<T>>::get(key)?;
+        while let Some(next) = <Redirect<T>>::get(uid) {
+            uid = next;
+        }
+        Some(uid)
+    }
+
     fn ensure_uid(key: &<T as System>::AccountId) -> Result<T::Uid, Error<T>> {
-        let uid = <UidLookup<T>>::get(&key).ok_or(Error::<T>::NoAccount)?;
+        let uid = Self::lookup(key).ok_or(Error::<T>::NoAccount)?;
+        if <Tombstone<T>>::get(uid) {
+            return Err(Error::<T>::AccountClosed);
+        }
         if !<Keys<T>>::get(uid).contains(key) {
             return Err(Error::<T>::Unauthorized);
         }
@@ -197,19 +583,120 @@ impl<T: Trait> Module<T> {
         }
     }
 
-    fn create_account(key: <T as System>::AccountId) -> Result<T::Uid, Error<T>> {
+    fn next_uid() -> Result<T::Uid, Error<T>> {
         let uid = <UidCounter<T>>::get();
         let next_uid = uid
             .checked_add(&1u8.into())
             .ok_or(Error::<T>::UidOverflow)?;
-        let gen = T::Gen::from(0u8);
         <UidCounter<T>>::put(next_uid);
+        Ok(uid)
+    }
+
+    fn create_account(key: <T as System>::AccountId) -> Result<T::Uid, Error<T>> {
+        let uid = Self::next_uid()?;
+        let gen = T::Gen::from(0u8);
         <PasswordGen<T>>::insert(uid, gen);
         Self::deposit_event(RawEvent::AccountCreated(uid));
         Self::add_key_to_uid(uid, key);
         Ok(uid)
     }
 
+    /// Returns the role of `member` in `team`.
+    pub fn role(team: T::Uid, member: T::Uid) -> Option<Role> {
+        <Members<T>>::get(team)
+            .into_iter()
+            .find(|(uid, _)| *uid == member)
+            .map(|(_, role)| role)
+    }
+
+    /// Returns the role in `team` of the account that `key` belongs to.
+    pub fn key_role(team: T::Uid, key: &<T as System>::AccountId) -> Option<Role> {
+        let uid = Self::ensure_uid(key).ok()?;
+        Self::role(team, uid)
+    }
+
+    fn ensure_role(team: T::Uid, uid: T::Uid, min: Role) -> Result<Role, Error<T>> {
+        match Self::role(team, uid) {
+            Some(role) if role >= min => Ok(role),
+            _ => Err(Error::<T>::Unauthorized),
+        }
+    }
+
+    fn ensure_owner_remains(team: T::Uid) -> Result<(), Error<T>> {
+        let owners = <Members<T>>::get(team)
+            .into_iter()
+            .filter(|(_, role)| *role == Role::Owner)
+            .count();
+        ensure!(owners > 1, Error::<T>::LastOwner);
+        Ok(())
+    }
+
+    /// Ensures that `uid` can have `count` more keys.
+    fn ensure_can_add_keys(uid: T::Uid, count: usize) -> Result<(), Error<T>> {
+        let keys = <Keys<T>>::get(uid).0.len().saturating_add(count);
+        ensure!(keys <= T::MaxKeys::get() as usize, Error::<T>::TooManyKeys);
+        Ok(())
+    }
+
+    /// Ensures that `team` can have `count` more members.
+    fn ensure_can_add_members(team: T::Uid, count: usize) -> Result<(), Error<T>> {
+        let members = <Members<T>>::get(team).len().saturating_add(count);
+        ensure!(
+            members <= T::MaxMembers::get() as usize,
+            Error::<T>::TooManyMembers
+        );
+        Ok(())
+    }
+
+    /// Ensures that `uid` can join `count` more teams.
+    fn ensure_can_join_teams(uid: T::Uid, count: usize) -> Result<(), Error<T>> {
+        let teams = <Teams<T>>::get(uid).len().saturating_add(count);
+        ensure!(
+            teams <= T::MaxTeams::get() as usize,
+            Error::<T>::TooManyTeams
+        );
+        Ok(())
+    }
+
+    /// Ensures that `uid` isn't the last owner of a team with other members.
+    fn ensure_can_leave_teams(uid: T::Uid) -> Result<(), Error<T>> {
+        for team in <Teams<T>>::get(uid) {
+            let members = <Members<T>>::get(team);
+            if members.len() > 1 && Self::role(team, uid) == Some(Role::Owner) {
+                Self::ensure_owner_remains(team)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes `uid` from all its teams.
+    fn leave_teams(uid: T::Uid) {
+        for team in <Teams<T>>::take(uid) {
+            <Members<T>>::mutate(team, |members| members.retain(|(member, _)| *member != uid));
+            Self::deposit_event(RawEvent::MemberRemoved(team, uid));
+            Self::revoke_team_key(team);
+        }
+    }
+
+    /// Moves the team memberships of `from` to `into`, keeping the higher
+    /// role when both are members of a team.
+    fn merge_teams(from: T::Uid, into: T::Uid) {
+        for team in <Teams<T>>::take(from) {
+            let role = Self::role(team, from).unwrap_or(Role::Reader);
+            let prev = Self::role(team, into);
+            let role = prev.map_or(role, |prev| prev.max(role));
+            <Members<T>>::mutate(team, |members| {
+                members.retain(|(uid, _)| *uid != from && *uid != into);
+                members.push((into, role));
+            });
+            if prev.is_none() {
+                <Teams<T>>::mutate(into, |teams| teams.push(team));
+            }
+            Self::deposit_event(RawEvent::MemberRemoved(team, from));
+            Self::deposit_event(RawEvent::MemberAdded(team, into, role));
+        }
+    }
+
     fn add_key_to_uid(uid: T::Uid, key: <T as System>::AccountId) {
         <UidLookup<T>>::insert(key.clone(), uid);
         <Keys<T>>::mutate(uid, |keys| keys.insert(key.clone()));
@@ -221,13 +708,125 @@ impl<T: Trait> Module<T> {
         // to an old key or the same key being added to a different account
         // after being revoked.
         <Keys<T>>::mutate(uid, |keys| keys.remove(&key));
+        <EncryptionKeys<T>>::remove(uid, &key);
         Self::deposit_event(RawEvent::KeyRemoved(uid, key));
+        for team in <Teams<T>>::get(uid) {
+            Self::revoke_team_key(team);
+        }
+    }
+
+    /// Marks the team key as readable by a removed member or device, so
+    /// that the remaining members rotate it.
+    fn revoke_team_key(team: T::Uid) {
+        if !<TeamKeyRevoked<T>>::get(team) {
+            <TeamKeyRevoked<T>>::insert(team, true);
+            Self::deposit_event(RawEvent::TeamKeyRevoked(team));
+        }
+    }
+
+    fn close_uid(
+        uid: T::Uid,
+        who: <T as System>::AccountId,
+        beneficiary: <T as System>::AccountId,
+    ) -> DispatchResult {
+        Self::ensure_can_leave_teams(uid)?;
+        Self::release_username(uid, &who);
+        Self::release_account_deposit(uid);
+        let balance = T::Currency::free_balance(&who);
+        T::Currency::transfer(
+            &who,
+            &beneficiary,
+            balance,
+            ExistenceRequirement::AllowDeath,
+        )?;
+        // The lookups are kept and point to the tombstone, so that the
+        // keys can't be used to impersonate the closed account.
+        <Tombstone<T>>::insert(uid, true);
+        <PendingClose<T>>::remove(uid);
+        <Keys<T>>::remove(uid);
+        <EncryptionKeys<T>>::remove_prefix(uid);
+        <Identity<T>>::remove(uid);
+        <PasswordGen<T>>::remove(uid);
+        <PasswordMask<T>>::remove_prefix(uid);
+        Self::leave_teams(uid);
+        Self::deposit_event(RawEvent::AccountClosed(uid, beneficiary));
+        Ok(())
+    }
+
+    fn release_username(uid: T::Uid, who: &<T as System>::AccountId) {
+        if let Some(username) = <Username<T>>::take(uid) {
+            <UsernameLookup<T>>::remove(&username);
+            T::Currency::unreserve(who, T::UsernameDeposit::get());
+            Self::deposit_event(RawEvent::UsernameCleared(uid, username));
+        }
+    }
+
+    fn release_account_deposit(uid: T::Uid) {
+        if let Some((depositor, deposit)) = <AccountDeposits<T>>::take(uid) {
+            T::Currency::unreserve(&depositor, deposit);
+        }
+    }
+
+    fn merge_uids(from: T::Uid, into: T::Uid) -> DispatchResult {
+        let from_keys = <Keys<T>>::get(from);
+        let into_keys = <Keys<T>>::get(into);
+        let username = <Username<T>>::get(from);
+        Self::release_account_deposit(from);
+        if let (Some(from_key), Some(into_key)) = (from_keys.0.first(), into_keys.0.first()) {
+            Self::release_username(from, from_key);
+            // Balances are stored per uid, so the first key holds the
+            // balance of all keys. Deposits reserved by other modules
+            // can't be moved.
+            ensure!(
+                T::Currency::reserved_balance(from_key).is_zero(),
+                Error::<T>::ReservedBalance
+            );
+            let balance = T::Currency::free_balance(from_key);
+            T::Currency::transfer(
+                from_key,
+                into_key,
+                balance,
+                ExistenceRequirement::AllowDeath,
+            )?;
+            // The username moves to the surviving account if it doesn't
+            // have one already.
+            if let Some(username) = username {
+                if <Username<T>>::get(into).is_none()
+                    && T::Currency::reserve(into_key, T::UsernameDeposit::get()).is_ok()
+                {
+                    <UsernameLookup<T>>::insert(username.clone(), into);
+                    <Username<T>>::insert(into, username.clone());
+                    Self::deposit_event(RawEvent::UsernameSet(into, username));
+                }
+            }
+        }
+        <Keys<T>>::remove(from);
+        for key in from_keys.0 {
+            if let Some(encryption_key) = <EncryptionKeys<T>>::take(from, &key) {
+                <EncryptionKeys<T>>::insert(into, &key, encryption_key);
+            }
+            Self::add_key_to_uid(into, key);
+        }
+        if let Some(cid) = <Identity<T>>::take(from) {
+            <Identity<T>>::insert(into, cid.clone());
+            Self::deposit_event(RawEvent::IdentityChanged(into, cid));
+        }
+        Self::merge_teams(from, into);
+        // Devices of the merged account need to be provisioned with
+        // the password of the surviving account.
+        <PasswordGen<T>>::remove(from);
+        <PasswordMask<T>>::remove_prefix(from);
+        <PendingClose<T>>::remove(from);
+        <PendingMerge<T>>::remove(from, into);
+        <Redirect<T>>::insert(from, into);
+        Self::deposit_event(RawEvent::AccountMerged(from, into));
+        Ok(())
     }
 }
 
 impl<T: Trait> StoredMap<<T as System>::AccountId, <T as Trait>::AccountData> for Module<T> {
     fn get(k: &<T as System>::AccountId) -> <T as Trait>::AccountData {
-        if let Some(uid) = <UidLookup<T>>::get(k) {
+        if let Some(uid) = Self::lookup(k) {
             <Account<T>>::get(&uid)
         } else {
             <T as Trait>::AccountData::default()
@@ -235,7 +834,7 @@ impl<T: Trait> StoredMap<<T as System>::AccountId, <T as Trait>::AccountData> fo
     }
 
     fn is_explicit(k: &<T as System>::AccountId) -> bool {
-        <UidLookup<T>>::get(k).is_some()
+        Self::lookup(k).is_some()
     }
 
     fn mutate<R>(
@@ -245,7 +844,7 @@ impl<T: Trait> StoredMap<<T as System>::AccountId, <T as Trait>::AccountData> fo
         if <UidLookup<T>>::get(k).is_none() {
             Self::create_account(k.clone()).ok();
         }
-        if let Some(uid) = <UidLookup<T>>::get(k) {
+        if let Some(uid) = Self::lookup(k) {
             <Account<T>>::mutate(&uid, f)
         } else {
             // This should only happen if uid overflows.
@@ -260,7 +859,7 @@ impl<T: Trait> StoredMap<<T as System>::AccountId, <T as Trait>::AccountData> fo
         if <UidLookup<T>>::get(k).is_none() {
             Self::create_account(k.clone()).ok();
         }
-        if let Some(uid) = <UidLookup<T>>::get(k) {
+        if let Some(uid) = Self::lookup(k) {
             <Account<T>>::mutate_exists(&uid, f)
         } else {
             // This should only happen if uid overflows.
@@ -275,7 +874,7 @@ impl<T: Trait> StoredMap<<T as System>::AccountId, <T as Trait>::AccountData> fo
         if <UidLookup<T>>::get(k).is_none() {
             Self::create_account(k.clone()).ok();
         }
-        if let Some(uid) = <UidLookup<T>>::get(k) {
+        if let Some(uid) = Self::lookup(k) {
             <Account<T>>::try_mutate_exists(&uid, f)
         } else {
             // This should only happen if uid overflows.
@@ -284,7 +883,7 @@ impl<T: Trait> StoredMap<<T as System>::AccountId, <T as Trait>::AccountData> fo
     }
 
     fn remove(k: &<T as System>::AccountId) {
-        if let Some(uid) = <UidLookup<T>>::get(k) {
+        if let Some(uid) = Self::lookup(k) {
             <Account<T>>::remove(&uid);
         }
     }
@@ -1,6 +1,8 @@
 use crate::{Module, Trait};
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_support::{impl_outer_origin, parameter_types, traits::Get, weights::Weight};
+use std::cell::RefCell;
 use frame_system as system;
+use pallet_balances as balances;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
@@ -48,15 +50,56 @@ impl system::Trait for Test {
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
 }
+parameter_types! {
+    pub const ExistentialDeposit: u128 = 500;
+    pub const MaxLocks: u32 = 50;
+}
+impl balances::Trait for Test {
+    type Balance = u128;
+    type Event = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type MaxLocks = MaxLocks;
+    type DustRemoval = ();
+    type AccountStore = IdentityModule;
+    type WeightInfo = ();
+}
+thread_local! {
+    static ACCOUNT_DEPOSIT: RefCell<u128> = RefCell::new(0);
+}
+pub struct AccountDeposit;
+impl Get<u128> for AccountDeposit {
+    fn get() -> u128 {
+        ACCOUNT_DEPOSIT.with(|v| *v.borrow())
+    }
+}
+pub fn set_account_deposit(deposit: u128) {
+    ACCOUNT_DEPOSIT.with(|v| *v.borrow_mut() = deposit);
+}
+parameter_types! {
+    pub const UsernameDeposit: u128 = 100;
+    pub const MaxPasswordMasks: u8 = 2;
+    pub const MaxKeys: u32 = 3;
+    pub const MaxTeams: u32 = 2;
+    pub const MaxMembers: u32 = 3;
+}
 impl Trait for Test {
     type Uid = u8;
     type Mask = [u8; 32];
     type Gen = u8;
+    type MaxPasswordMasks = MaxPasswordMasks;
     type Cid = u32;
-    type AccountData = ();
+    type AccountData = balances::AccountData<u128>;
+    type Currency = BalancesModule;
+    type AccountDeposit = AccountDeposit;
+    type UsernameDeposit = UsernameDeposit;
+    type MaxKeys = MaxKeys;
+    type MaxTeams = MaxTeams;
+    type MaxMembers = MaxMembers;
+    type WeightInfo = ();
     type Event = ();
 }
 pub type IdentityModule = Module<Test>;
+pub type BalancesModule = balances::Module<Test>;
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
     system::GenesisConfig::default()
@@ -1,5 +1,7 @@
 use crate::mock::*;
-use frame_support::assert_ok;
+use crate::{Error, Role, UidCounter};
+use frame_support::traits::{LockableCurrency, ReservableCurrency, WithdrawReasons};
+use frame_support::{assert_ok, StorageValue};
 
 #[test]
 fn set_identity() {
@@ -22,6 +24,25 @@ fn set_identity() {
     });
 }
 
+#[test]
+fn set_encryption_key() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        assert!(IdentityModule::set_encryption_key(key1.clone(), [1; 32]).is_err());
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::add_key(key1.clone(), 2));
+        assert_ok!(IdentityModule::set_encryption_key(key1.clone(), [1; 32]));
+        assert_ok!(IdentityModule::set_encryption_key(key2, [2; 32]));
+        assert_eq!(IdentityModule::encryption_key(0, 1), Some([1; 32]));
+        assert_eq!(IdentityModule::encryption_key(0, 2), Some([2; 32]));
+
+        assert_ok!(IdentityModule::remove_key(key1, 2));
+        assert_eq!(IdentityModule::encryption_key(0, 2), None);
+        assert_eq!(IdentityModule::encryption_key(0, 1), Some([1; 32]));
+    });
+}
+
 #[test]
 fn change_password() {
     new_test_ext().execute_with(|| {
@@ -30,6 +51,459 @@ fn change_password() {
         assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
         assert_ok!(IdentityModule::add_key(key1, 2));
         assert!(IdentityModule::change_password(key2.clone(), [0; 32], 0).is_err());
-        assert_ok!(IdentityModule::change_password(key2, [0; 32], 1));
+        assert_ok!(IdentityModule::change_password(key2.clone(), [0; 32], 1));
+        assert_ok!(IdentityModule::change_password(key2.clone(), [0; 32], 2));
+        assert_eq!(IdentityModule::mask(0, 1), Some([0; 32]));
+        assert_ok!(IdentityModule::change_password(key2, [0; 32], 3));
+        assert_eq!(IdentityModule::mask(0, 1), None);
+        assert_eq!(IdentityModule::mask(0, 2), Some([0; 32]));
+        assert_eq!(IdentityModule::mask(0, 3), Some([0; 32]));
+    });
+}
+
+#[test]
+fn close_account() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::add_key(key1.clone(), 2));
+        assert_ok!(IdentityModule::set_identity(key1.clone(), None, 42));
+        BalancesModule::make_free_balance_be(&1, 1000);
+
+        assert!(IdentityModule::close_account(key1.clone(), 2).is_err());
+        assert_ok!(IdentityModule::close_account(key1.clone(), 3));
+        assert_eq!(IdentityModule::pending_close(0), Some((1, 3)));
+        // Confirming with the same key doesn't close the account.
+        assert_ok!(IdentityModule::close_account(key1.clone(), 3));
+        assert!(!IdentityModule::tombstone(0));
+
+        // A failed transfer leaves the account untouched.
+        assert_ok!(IdentityModule::set_username(
+            key1.clone(),
+            b"alice".to_vec()
+        ));
+        BalancesModule::set_lock(*b"testlock", &1, 500, WithdrawReasons::all());
+        assert!(IdentityModule::close_account(key2.clone(), 3).is_err());
+        assert!(!IdentityModule::tombstone(0));
+        assert_eq!(IdentityModule::username(0), Some(b"alice".to_vec()));
+        assert_eq!(BalancesModule::reserved_balance(&1), 100);
+        BalancesModule::remove_lock(*b"testlock", &1);
+
+        assert_ok!(IdentityModule::close_account(key2.clone(), 3));
+        assert!(IdentityModule::tombstone(0));
+        assert_eq!(IdentityModule::username(0), None);
+        assert_eq!(BalancesModule::free_balance(&3), 1000);
+        assert_eq!(BalancesModule::free_balance(&1), 0);
+        assert!(IdentityModule::keys(0).0.is_empty());
+        assert_eq!(IdentityModule::identity(0), None);
+        assert_eq!(IdentityModule::key(1), Some(0));
+
+        assert!(IdentityModule::add_key(key1, 4).is_err());
+        assert!(IdentityModule::create_account_for(key2, 1).is_err());
+    });
+}
+
+#[test]
+fn merge_account() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 2));
+        assert_ok!(IdentityModule::set_identity(key2.clone(), None, 42));
+        BalancesModule::make_free_balance_be(&1, 1000);
+        BalancesModule::make_free_balance_be(&2, 1000);
+
+        assert!(IdentityModule::merge_account(key1.clone(), 1, 1, 1, 2).is_err());
+        assert_eq!(
+            IdentityModule::merge_account(key2.clone(), 1, 0, 0, 0),
+            Err(Error::<Test>::InvalidWitness.into())
+        );
+        assert_ok!(IdentityModule::merge_account(key2.clone(), 1, 0, 1, 2));
+        assert_eq!(IdentityModule::pending_merge(1, 0), Some(1));
+        assert_ok!(IdentityModule::merge_account(key2.clone(), 1, 0, 1, 2));
+        assert_eq!(IdentityModule::redirect(1), None);
+
+        assert_ok!(IdentityModule::merge_account(key1.clone(), 1, 0, 1, 2));
+        assert_eq!(IdentityModule::redirect(1), Some(0));
+        assert_eq!(IdentityModule::keys(0).0, vec![1, 2]);
+        assert!(IdentityModule::keys(1).0.is_empty());
+        assert_eq!(IdentityModule::identity(0), Some(42));
+        assert_eq!(IdentityModule::identity(1), None);
+        assert_eq!(BalancesModule::free_balance(&1), 2000);
+        assert_eq!(BalancesModule::free_balance(&2), 2000);
+
+        assert_ok!(IdentityModule::set_identity(key2, Some(42), 43));
+        assert!(IdentityModule::merge_account(key1, 0, 1, 1, 2).is_err());
+    });
+}
+
+#[test]
+fn merge_team_member() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        let key3 = Origin::signed(3);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 2));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 3));
+        assert_ok!(IdentityModule::create_team(key1.clone()));
+        assert_ok!(IdentityModule::create_team(key3.clone()));
+        let (team1, team2) = (3, 4);
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team1,
+            1,
+            Role::Admin
+        ));
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team1,
+            2,
+            Role::Reader
+        ));
+        assert_ok!(IdentityModule::add_member(
+            key3.clone(),
+            team2,
+            1,
+            Role::Writer
+        ));
+        BalancesModule::make_free_balance_be(&2, 1000);
+        assert_ok!(IdentityModule::set_username(
+            key2.clone(),
+            b"alice".to_vec()
+        ));
+        assert_ok!(BalancesModule::reserve(&2, 300));
+
+        // Deposits of other modules block the merge.
+        assert_ok!(IdentityModule::merge_account(key2, 1, 2, 1, 2));
+        assert_eq!(
+            IdentityModule::merge_account(key3.clone(), 1, 2, 1, 2),
+            Err(Error::<Test>::ReservedBalance.into())
+        );
+        assert_eq!(IdentityModule::redirect(1), None);
+        assert_eq!(IdentityModule::username(1), Some(b"alice".to_vec()));
+        assert_eq!(BalancesModule::reserved_balance(&2), 400);
+
+        BalancesModule::unreserve(&2, 300);
+        assert_ok!(IdentityModule::merge_account(key3, 1, 2, 1, 2));
+        assert_eq!(IdentityModule::role(team1, 1), None);
+        assert_eq!(IdentityModule::role(team1, 2), Some(Role::Admin));
+        assert_eq!(IdentityModule::role(team2, 2), Some(Role::Owner));
+        assert!(IdentityModule::teams(1).is_empty());
+        assert_eq!(IdentityModule::teams(2), vec![team2, team1]);
+        assert_eq!(IdentityModule::members(team1).len(), 2);
+        assert_eq!(IdentityModule::username(2), Some(b"alice".to_vec()));
+        assert_eq!(BalancesModule::reserved_balance(&3), 100);
+        assert_eq!(BalancesModule::free_balance(&3), 900);
+    });
+}
+
+#[test]
+fn set_username() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 2));
+        BalancesModule::make_free_balance_be(&1, 1000);
+        BalancesModule::make_free_balance_be(&2, 1000);
+
+        assert!(IdentityModule::set_username(key1.clone(), b"a".to_vec()).is_err());
+        assert_ok!(IdentityModule::set_username(key1.clone(), b"Alice".to_vec()));
+        assert_eq!(IdentityModule::username(0), Some(b"alice".to_vec()));
+        assert_eq!(IdentityModule::username_lookup(b"alice".to_vec()), Some(0));
+        assert_eq!(BalancesModule::reserved_balance(&1), 100);
+        assert!(IdentityModule::set_username(key2.clone(), b"alice".to_vec()).is_err());
+
+        assert_ok!(IdentityModule::set_username(key1.clone(), b"alice2".to_vec()));
+        assert_eq!(IdentityModule::username_lookup(b"alice".to_vec()), None);
+        assert_eq!(BalancesModule::reserved_balance(&1), 100);
+
+        assert_ok!(IdentityModule::clear_username(key1.clone()));
+        assert_eq!(IdentityModule::username(0), None);
+        assert_eq!(BalancesModule::reserved_balance(&1), 0);
+        assert!(IdentityModule::clear_username(key1).is_err());
+        assert_ok!(IdentityModule::set_username(key2, b"alice".to_vec()));
+    });
+}
+
+#[test]
+fn account_deposit() {
+    new_test_ext().execute_with(|| {
+        set_account_deposit(100);
+        BalancesModule::make_free_balance_be(&5, 1000);
+        assert!(IdentityModule::create_account_for(Origin::signed(6), 1).is_err());
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(5), 1));
+        assert_eq!(IdentityModule::account_deposit(1), Some((5, 100)));
+        assert_eq!(BalancesModule::reserved_balance(&5), 100);
+
+        assert_ok!(IdentityModule::add_key(Origin::signed(1), 2));
+        assert_ok!(IdentityModule::close_account(Origin::signed(1), 5));
+        assert_ok!(IdentityModule::close_account(Origin::signed(2), 5));
+        assert_eq!(IdentityModule::account_deposit(1), None);
+        assert_eq!(BalancesModule::reserved_balance(&5), 0);
+
+        // A failed account creation doesn't keep the deposit.
+        UidCounter::<Test>::put(u8::max_value());
+        assert_eq!(
+            IdentityModule::create_account_for(Origin::signed(5), 3),
+            Err(Error::<Test>::UidOverflow.into())
+        );
+        assert_eq!(BalancesModule::reserved_balance(&5), 0);
+    });
+}
+
+#[test]
+fn teams() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        let key3 = Origin::signed(3);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 2));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 3));
+        assert_ok!(IdentityModule::create_team(key1.clone()));
+        let team = 3;
+        assert_eq!(IdentityModule::role(team, 0), Some(Role::Owner));
+        assert_eq!(IdentityModule::key_role(team, &1), Some(Role::Owner));
+
+        assert!(IdentityModule::add_member(key2.clone(), team, 2, Role::Reader).is_err());
+        assert!(IdentityModule::add_member(key1.clone(), team, team, Role::Reader).is_err());
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team,
+            1,
+            Role::Admin
+        ));
+        assert_eq!(IdentityModule::key_role(team, &2), Some(Role::Admin));
+
+        // Admins can't grant roles above their own.
+        assert!(IdentityModule::add_member(key2.clone(), team, 2, Role::Owner).is_err());
+        assert_ok!(IdentityModule::add_member(
+            key2.clone(),
+            team,
+            2,
+            Role::Writer
+        ));
+        assert!(IdentityModule::remove_member(key2.clone(), team, 0).is_err());
+        assert!(IdentityModule::remove_member(key3.clone(), team, 1).is_err());
+
+        // The last owner can't leave.
+        assert_eq!(
+            IdentityModule::remove_member(key1.clone(), team, 0),
+            Err(Error::<Test>::LastOwner.into())
+        );
+        assert_ok!(IdentityModule::remove_member(key3, team, 2));
+        assert_ok!(IdentityModule::remove_member(key1, team, 1));
+        assert_eq!(IdentityModule::members(team), vec![(0, Role::Owner)]);
+    });
+}
+
+#[test]
+fn max_keys() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 4));
+        assert_ok!(IdentityModule::add_key(key1.clone(), 2));
+        assert_ok!(IdentityModule::add_key(key1.clone(), 3));
+        assert_eq!(
+            IdentityModule::add_key(key1.clone(), 5),
+            Err(Error::<Test>::TooManyKeys.into())
+        );
+        assert_eq!(
+            IdentityModule::merge_account(Origin::signed(4), 1, 0, 1, 0),
+            Err(Error::<Test>::TooManyKeys.into())
+        );
+
+        assert_ok!(IdentityModule::remove_key(key1.clone(), 3));
+        assert_ok!(IdentityModule::add_key(key1, 5));
+    });
+}
+
+#[test]
+fn max_teams() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        let key3 = Origin::signed(3);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 2));
+        assert_ok!(IdentityModule::create_team(key1.clone()));
+        assert_ok!(IdentityModule::create_team(key1.clone()));
+        let (team1, team2) = (2, 3);
+        assert_eq!(
+            IdentityModule::create_team(key1.clone()),
+            Err(Error::<Test>::TooManyTeams.into())
+        );
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team1,
+            1,
+            Role::Reader
+        ));
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team2,
+            1,
+            Role::Reader
+        ));
+        // Changing the role doesn't join another team.
+        assert_ok!(IdentityModule::add_member(key1, team2, 1, Role::Writer));
+        assert_eq!(
+            IdentityModule::create_team(key2.clone()),
+            Err(Error::<Test>::TooManyTeams.into())
+        );
+
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 3));
+        assert_ok!(IdentityModule::create_team(key3.clone()));
+        assert_eq!(
+            IdentityModule::merge_account(key3, 4, 1, 1, 2),
+            Err(Error::<Test>::TooManyTeams.into())
+        );
+        assert!(IdentityModule::pending_merge(4, 1).is_none());
+    });
+}
+
+#[test]
+fn max_members() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 2));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 3));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 4));
+        assert_ok!(IdentityModule::create_team(key1.clone()));
+        let team = 4;
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team,
+            1,
+            Role::Reader
+        ));
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team,
+            2,
+            Role::Reader
+        ));
+        assert_eq!(
+            IdentityModule::add_member(key1.clone(), team, 3, Role::Reader),
+            Err(Error::<Test>::TooManyMembers.into())
+        );
+        // Changing the role doesn't add another member.
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team,
+            2,
+            Role::Writer
+        ));
+
+        assert_ok!(IdentityModule::remove_member(key1.clone(), team, 2));
+        assert_ok!(IdentityModule::add_member(key1, team, 3, Role::Reader));
+    });
+}
+
+#[test]
+fn team_keys() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        let key3 = Origin::signed(3);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 2));
+        assert_ok!(IdentityModule::create_team(key1.clone()));
+        let team = 2;
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team,
+            1,
+            Role::Reader
+        ));
+        assert_eq!(IdentityModule::teams(1), vec![team]);
+
+        // Readers can't rotate the key.
+        assert_eq!(
+            IdentityModule::set_team_key(key2.clone(), team, 1, 42),
+            Err(Error::<Test>::Unauthorized.into())
+        );
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team,
+            1,
+            Role::Writer
+        ));
+        assert!(IdentityModule::set_team_key(key3.clone(), team, 1, 42).is_err());
+        assert_eq!(
+            IdentityModule::set_team_key(key2.clone(), team, 2, 42),
+            Err(Error::<Test>::TeamKeyGenMissmatch.into())
+        );
+        assert_ok!(IdentityModule::set_team_key(key2.clone(), team, 1, 42));
+        assert_eq!(IdentityModule::team_key_gen(team), 1);
+        assert_eq!(IdentityModule::team_key(team, 1), Some(42));
+        assert!(!IdentityModule::team_key_revoked(team));
+
+        // Removing a device of a member revokes the key.
+        assert_ok!(IdentityModule::add_key(key2.clone(), 3));
+        assert_ok!(IdentityModule::remove_key(key2.clone(), 3));
+        assert!(IdentityModule::team_key_revoked(team));
+        assert_ok!(IdentityModule::set_team_key(key1.clone(), team, 2, 43));
+        assert!(!IdentityModule::team_key_revoked(team));
+
+        // Removing a member revokes the key.
+        assert_ok!(IdentityModule::remove_member(key1.clone(), team, 1));
+        assert!(IdentityModule::team_key_revoked(team));
+        assert!(IdentityModule::teams(1).is_empty());
+        assert!(IdentityModule::set_team_key(key2, team, 3, 44).is_err());
+        assert_ok!(IdentityModule::set_team_key(key1, team, 3, 44));
+        assert_eq!(IdentityModule::team_key(team, 2), Some(43));
+    });
+}
+
+#[test]
+fn close_team_member() {
+    new_test_ext().execute_with(|| {
+        let key1 = Origin::signed(1);
+        let key2 = Origin::signed(2);
+        let key3 = Origin::signed(3);
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 1));
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 2));
+        assert_ok!(IdentityModule::add_key(key2.clone(), 3));
+        assert_ok!(IdentityModule::create_team(key1.clone()));
+        let team = 2;
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team,
+            1,
+            Role::Owner
+        ));
+        assert_ok!(IdentityModule::set_team_key(key1.clone(), team, 1, 42));
+
+        assert_ok!(IdentityModule::close_account(key2, 4));
+        assert_ok!(IdentityModule::close_account(key3, 4));
+        assert!(IdentityModule::tombstone(1));
+        assert_eq!(IdentityModule::members(team), vec![(0, Role::Owner)]);
+        assert!(IdentityModule::teams(1).is_empty());
+        assert!(IdentityModule::team_key_revoked(team));
+        assert_ok!(IdentityModule::set_team_key(key1.clone(), team, 2, 43));
+        assert!(!IdentityModule::team_key_revoked(team));
+
+        // The last owner of a team with other members can't close the account.
+        assert_ok!(IdentityModule::create_account_for(Origin::signed(0), 5));
+        assert_ok!(IdentityModule::add_member(
+            key1.clone(),
+            team,
+            3,
+            Role::Reader
+        ));
+        assert_ok!(IdentityModule::add_key(key1.clone(), 6));
+        assert_ok!(IdentityModule::close_account(key1, 4));
+        assert_eq!(
+            IdentityModule::close_account(Origin::signed(6), 4),
+            Err(Error::<Test>::LastOwner.into())
+        );
+        assert!(!IdentityModule::tombstone(0));
     });
 }
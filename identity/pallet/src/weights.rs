@@ -0,0 +1,177 @@
+//! Weights for the identity module.
+//!
+//! The numbers are hand written placeholders estimated from the storage
+//! accesses of each call, they haven't been measured. Replace them with
+//! the output of
+//!
+//! ```sh
+//! cargo run --release -p test-node --features runtime-benchmarks -- \
+//!     benchmark --chain dev --execution wasm --wasm-execution compiled \
+//!     --pallet sunshine_identity_pallet --extrinsic '*' --steps 50 \
+//!     --repeat 20 --output identity/pallet/src/weights.rs
+//! ```
+//!
+//! for the target hardware before relying on them.
+//!
+//! Each entry removed by a `remove_prefix` is counted as a write, assuming
+//! at most 16 password masks per account.
+#![allow(unused_parens)]
+
+use frame_support::traits::Get;
+use frame_support::weights::Weight;
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed by the identity module.
+pub trait WeightInfo {
+    fn create_account_for() -> Weight;
+    fn add_key() -> Weight;
+    fn remove_key(t: u32) -> Weight;
+    fn set_encryption_key() -> Weight;
+    fn change_password() -> Weight;
+    fn set_identity() -> Weight;
+    fn close_account(k: u32, t: u32) -> Weight;
+    fn set_username() -> Weight;
+    fn clear_username() -> Weight;
+    fn merge_account(k: u32, t: u32) -> Weight;
+    fn create_team() -> Weight;
+    fn add_member(m: u32) -> Weight;
+    fn remove_member(m: u32) -> Weight;
+    fn set_team_key() -> Weight;
+}
+
+/// Placeholder weights for the identity module.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+
+impl<T: frame_system::Trait> WeightInfo for SubstrateWeight<T> {
+    fn create_account_for() -> Weight {
+        (61_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn add_key() -> Weight {
+        (34_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn remove_key(t: u32) -> Weight {
+        (35_000_000 as Weight)
+            .saturating_add((4_000_000 as Weight).saturating_mul(t as Weight))
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(t as Weight)))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(t as Weight)))
+    }
+    fn set_encryption_key() -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn change_password() -> Weight {
+        (36_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn set_identity() -> Weight {
+        (31_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn close_account(k: u32, t: u32) -> Weight {
+        (118_000_000 as Weight)
+            .saturating_add((1_000_000 as Weight).saturating_mul(k as Weight))
+            .saturating_add((9_000_000 as Weight).saturating_mul(t as Weight))
+            .saturating_add(T::DbWeight::get().reads(12 as Weight))
+            .saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(t as Weight)))
+            .saturating_add(T::DbWeight::get().writes(28 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(k as Weight)))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(t as Weight)))
+    }
+    fn set_username() -> Weight {
+        (52_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    fn clear_username() -> Weight {
+        (47_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn merge_account(k: u32, t: u32) -> Weight {
+        (131_000_000 as Weight)
+            .saturating_add((9_000_000 as Weight).saturating_mul(k as Weight))
+            .saturating_add((10_000_000 as Weight).saturating_mul(t as Weight))
+            .saturating_add(T::DbWeight::get().reads(14 as Weight))
+            .saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(t as Weight)))
+            .saturating_add(T::DbWeight::get().writes(30 as Weight))
+            .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(k as Weight)))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(t as Weight)))
+    }
+    fn create_team() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn add_member(m: u32) -> Weight {
+        (43_000_000 as Weight)
+            .saturating_add((1_000_000 as Weight).saturating_mul(m as Weight))
+            .saturating_add(T::DbWeight::get().reads(7 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn remove_member(m: u32) -> Weight {
+        (42_000_000 as Weight)
+            .saturating_add((1_000_000 as Weight).saturating_mul(m as Weight))
+            .saturating_add(T::DbWeight::get().reads(7 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn set_team_key() -> Weight {
+        (36_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn create_account_for() -> Weight {
+        0
+    }
+    fn add_key() -> Weight {
+        0
+    }
+    fn remove_key(_t: u32) -> Weight {
+        0
+    }
+    fn set_encryption_key() -> Weight {
+        0
+    }
+    fn change_password() -> Weight {
+        0
+    }
+    fn set_identity() -> Weight {
+        0
+    }
+    fn close_account(_k: u32, _t: u32) -> Weight {
+        0
+    }
+    fn set_username() -> Weight {
+        0
+    }
+    fn clear_username() -> Weight {
+        0
+    }
+    fn merge_account(_k: u32, _t: u32) -> Weight {
+        0
+    }
+    fn create_team() -> Weight {
+        0
+    }
+    fn add_member(_m: u32) -> Weight {
+        0
+    }
+    fn remove_member(_m: u32) -> Weight {
+        0
+    }
+    fn set_team_key() -> Weight {
+        0
+    }
+}
@@ -1,9 +1,116 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
+use sp_std::prelude::*;
 
 #[derive(Clone, Debug, Eq, PartialEq, Decode, Encode)]
 pub struct DeviceMaskData<M, G> {
     pub mask: M,
     pub gen: G,
 }
+
+/// Role of a member of a team.
+///
+/// Roles are ordered, every role has the permissions of the roles before it.
+#[derive(Clone, Copy, Debug, Decode, Encode, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Role {
+    /// Can read the team data.
+    Reader,
+    /// Can write the team data.
+    Writer,
+    /// Can add and remove members.
+    Admin,
+    /// Can add and remove admins and owners.
+    Owner,
+}
+
+impl core::fmt::Display for Role {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let role = match self {
+            Self::Reader => "reader",
+            Self::Writer => "writer",
+            Self::Admin => "admin",
+            Self::Owner => "owner",
+        };
+        write!(f, "{}", role)
+    }
+}
+
+impl core::str::FromStr for Role {
+    type Err = RoleParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Ok(match string {
+            "reader" => Self::Reader,
+            "writer" => Self::Writer,
+            "admin" => Self::Admin,
+            "owner" => Self::Owner,
+            _ => return Err(RoleParseError),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RoleParseError;
+
+impl core::fmt::Display for RoleParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "expected one of reader, writer, admin or owner")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RoleParseError {}
+
+/// Minimum length of a username.
+pub const MIN_USERNAME_LENGTH: usize = 3;
+
+/// Maximum length of a username.
+pub const MAX_USERNAME_LENGTH: usize = 32;
+
+/// Normalizes and validates a username.
+///
+/// Usernames are case insensitive and stored in lower case. A valid
+/// username starts with a letter and contains only ascii letters, digits
+/// and underscores.
+pub fn normalize_username(username: &[u8]) -> Option<Vec<u8>> {
+    if username.len() < MIN_USERNAME_LENGTH || username.len() > MAX_USERNAME_LENGTH {
+        return None;
+    }
+    if !username[0].is_ascii_alphabetic() {
+        return None;
+    }
+    if !username
+        .iter()
+        .all(|c| c.is_ascii_alphanumeric() || *c == b'_')
+    {
+        return None;
+    }
+    Some(username.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role() {
+        for role in &[Role::Reader, Role::Writer, Role::Admin, Role::Owner] {
+            assert_eq!(role.to_string().parse::<Role>().unwrap(), *role);
+        }
+        assert!("root".parse::<Role>().is_err());
+        assert!(Role::Admin > Role::Writer);
+        assert_eq!(Role::Owner.encode(), vec![3]);
+    }
+
+    #[test]
+    fn test_normalize_username() {
+        assert_eq!(normalize_username(b"Alice"), Some(b"alice".to_vec()));
+        assert_eq!(normalize_username(b"bob_42"), Some(b"bob_42".to_vec()));
+        assert_eq!(normalize_username(b"al"), None);
+        assert_eq!(normalize_username(b"42bob"), None);
+        assert_eq!(normalize_username(b"_bob"), None);
+        assert_eq!(normalize_username(b"bob@github"), None);
+        assert_eq!(normalize_username(&[b'a'; 33]), None);
+    }
+}